@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::error::Result;
+use crate::store::RepoKey;
+
+/// The environment variable marking a process started by
+/// [`ReleaseNotifier::spawn_background_refresh`](crate::ReleaseNotifier::spawn_background_refresh).
+///
+/// Its value identifies the repository to refresh.
+pub const BACKGROUND_REFRESH_ENV: &str = "RELNOTIFY_BACKGROUND_REFRESH";
+
+/// Returns true if this process was started to refresh the given repository.
+pub(crate) fn is_background_refresh(repo_key: &RepoKey) -> bool {
+    std::env::var(BACKGROUND_REFRESH_ENV).is_ok_and(|value| value == repo_key.to_string())
+}
+
+/// Returns the path whose lock is held while a background refresh runs.
+///
+/// It sits next to the cache file if there is one, and in the temp directory otherwise.
+pub(crate) fn refresh_lock_path(cache_file_path: Option<&str>, repo_key: &RepoKey) -> PathBuf {
+    match cache_file_path {
+        Some(path) => PathBuf::from(format!("{}.refresh", path)),
+        None => std::env::temp_dir().join(format!("relnotify-{}.refresh", repo_key.file_name())),
+    }
+}
+
+/// Starts `command` as a background refresh of the repository, detached from
+/// the terminal so it outlives the current process and isn't sent its signals.
+pub(crate) fn spawn_detached(mut command: Command, repo_key: &RepoKey) -> Result<()> {
+    command
+        .env(BACKGROUND_REFRESH_ENV, repo_key.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    // The child is never waited on; once this process exits it is reparented
+    command.spawn()?;
+    Ok(())
+}
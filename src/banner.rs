@@ -0,0 +1,538 @@
+//! Renders a [`VersionCheckResult`] as a boxed terminal banner.
+//!
+//! ```no_run
+//! # async fn run(notifier: relnotify::ReleaseNotifier) -> relnotify::Result<()> {
+//! use relnotify::banner::Banner;
+//!
+//! let result = notifier.check_version("1.0.0", false).await?;
+//! Banner::new()
+//!     .upgrade_command("cargo install my-cli")
+//!     .eprint(&result);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{IsTerminal, Write};
+
+use chrono::{DateTime, Utc};
+use terminal_size::{terminal_size_of, Width};
+
+use crate::types::{UpdateKind, VersionCheckResult};
+
+/// The width used when the terminal width can't be determined.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The narrowest the text inside a banner is wrapped to.
+const MIN_TEXT_WIDTH: usize = 20;
+
+/// Spaces between the border and the text on each side.
+const PADDING: usize = 2;
+
+/// ANSI escape sequences used for colors.
+const YELLOW: &str = "\x1b[33m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// The characters a banner's border is drawn with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BoxStyle {
+    /// Rounded Unicode box-drawing characters.
+    #[default]
+    Unicode,
+    /// Plain ASCII (`+`, `-`, `|`), for terminals without Unicode support.
+    Ascii,
+    /// No border.
+    None,
+}
+
+/// Whether a banner uses ANSI colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColorChoice {
+    /// Colors if stderr is a terminal, unless disabled through `NO_COLOR` or
+    /// `CLICOLOR=0`. `CLICOLOR_FORCE` enables them regardless.
+    #[default]
+    Auto,
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+}
+
+/// Options for rendering an update banner.
+///
+/// Templates may use these placeholders:
+/// - `{current}`: the current version
+/// - `{latest}`: the latest release's tag
+/// - `{kind}`: the size of the update ("major", "minor", "patch" or "prerelease")
+/// - `{age}`: how long ago the latest release was published, e.g. "3 days ago"
+/// - `{url}`: the latest release's page
+/// - `{command}`: the upgrade command
+///
+/// A template line using a placeholder without a value (e.g. `{command}` with
+/// no upgrade command set) is left out.
+#[derive(Debug, Clone, Default)]
+pub struct Banner {
+    /// The banner text, or None for the default message.
+    pub template: Option<String>,
+    /// The command that upgrades to the latest version.
+    pub upgrade_command: Option<String>,
+    /// The border style.
+    pub style: BoxStyle,
+    /// Whether to use colors.
+    pub color: ColorChoice,
+    /// The width to wrap to, or None to use the terminal width.
+    pub width: Option<usize>,
+}
+
+impl Banner {
+    /// Creates a banner with the default message, a Unicode border and automatic colors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the banner text; see [`Banner`] for the placeholders.
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Sets the command that upgrades to the latest version.
+    pub fn upgrade_command(mut self, command: impl Into<String>) -> Self {
+        self.upgrade_command = Some(command.into());
+        self
+    }
+
+    /// Sets the border style.
+    pub fn style(mut self, style: BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets whether to use colors.
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the width to wrap to instead of the terminal width.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Renders the banner for a check result.
+    ///
+    /// # Returns
+    /// The banner, or None if no update is available.
+    pub fn render(&self, result: &VersionCheckResult) -> Option<String> {
+        self.render_at(result, Utc::now())
+    }
+
+    /// Renders the banner as of `now`, which the release age is measured from.
+    pub fn render_at(&self, result: &VersionCheckResult, now: DateTime<Utc>) -> Option<String> {
+        let latest = result
+            .latest_release
+            .as_ref()
+            .filter(|_| result.update_available)?;
+
+        let values = [
+            ("current", Some(result.current_version.base.clone())),
+            ("latest", Some(latest.tag_name.clone())),
+            (
+                "kind",
+                result.update_kind.map(|kind| kind_name(kind).to_string()),
+            ),
+            ("age", latest.published_at.map(|at| relative_age(at, now))),
+            ("url", Some(latest.html_url.clone())),
+            ("command", self.upgrade_command.clone()),
+        ];
+
+        let template = self.template.as_deref().unwrap_or(match self.style {
+            BoxStyle::Ascii => DEFAULT_ASCII_TEMPLATE,
+            _ => DEFAULT_TEMPLATE,
+        });
+        let lines: Vec<String> = template
+            .lines()
+            .filter_map(|line| fill_line(line, &values))
+            .collect();
+
+        // Highlight the versions and command wherever they appear
+        let colors = self.colors_enabled().then(|| {
+            [
+                (result.current_version.base.as_str(), DIM),
+                (latest.tag_name.as_str(), GREEN),
+                (self.upgrade_command.as_deref().unwrap_or_default(), CYAN),
+            ]
+        });
+
+        Some(self.draw(&lines, colors.as_ref().map(|c| c.as_slice())))
+    }
+
+    /// Prints the banner to stderr, if an update is available and stderr is a terminal.
+    ///
+    /// # Returns
+    /// True if the banner was printed.
+    pub fn eprint(&self, result: &VersionCheckResult) -> bool {
+        let mut stderr = std::io::stderr();
+        if !stderr.is_terminal() {
+            return false;
+        }
+
+        match self.render(result) {
+            Some(banner) => writeln!(stderr, "{}", banner).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Returns whether colors are used, resolving [`ColorChoice::Auto`].
+    fn colors_enabled(&self) -> bool {
+        match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => colors_allowed(std::io::stderr().is_terminal(), |name| {
+                std::env::var(name).ok()
+            }),
+        }
+    }
+
+    /// Wraps, centers and boxes the lines, coloring the highlighted values.
+    fn draw(&self, lines: &[String], highlights: Option<&[(&str, &str)]>) -> String {
+        let bordered = self.style != BoxStyle::None;
+        let width = self.width.unwrap_or_else(terminal_width);
+        let overhead = if bordered { 2 + 2 * PADDING } else { 0 };
+        let max_text = width.saturating_sub(overhead).max(MIN_TEXT_WIDTH);
+
+        let wrapped: Vec<String> = lines.iter().flat_map(|line| wrap(line, max_text)).collect();
+        let text_width = wrapped.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let paint = |text: &str, color: &str| match highlights {
+            Some(_) if !text.is_empty() => format!("{}{}{}", color, text, RESET),
+            _ => text.to_string(),
+        };
+        let centered = |line: &str| {
+            let gap = text_width - line.chars().count();
+            let body = highlight(line, highlights.unwrap_or_default());
+            format!(
+                "{}{}{}",
+                " ".repeat(gap / 2),
+                body,
+                " ".repeat(gap - gap / 2)
+            )
+        };
+
+        if !bordered {
+            return wrapped
+                .iter()
+                .map(|line| centered(line).trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            match self.style {
+                BoxStyle::Ascii => ["+", "+", "+", "+", "-", "|"],
+                _ => ["╭", "╮", "╰", "╯", "─", "│"],
+            };
+        let inner = text_width + 2 * PADDING;
+        let side = paint(vertical, YELLOW);
+        let pad = " ".repeat(PADDING);
+        let blank = format!("{}{}{}", side, " ".repeat(inner), side);
+
+        let mut out = vec![
+            paint(
+                &format!("{}{}{}", top_left, horizontal.repeat(inner), top_right),
+                YELLOW,
+            ),
+            blank.clone(),
+        ];
+        out.extend(
+            wrapped
+                .iter()
+                .map(|line| format!("{}{}{}{}{}", side, pad, centered(line), pad, side)),
+        );
+        out.push(blank);
+        out.push(paint(
+            &format!(
+                "{}{}{}",
+                bottom_left,
+                horizontal.repeat(inner),
+                bottom_right
+            ),
+            YELLOW,
+        ));
+        out.join("\n")
+    }
+}
+
+/// The default message for Unicode and borderless banners.
+const DEFAULT_TEMPLATE: &str = "Update available {current} → {latest}\n\
+                                Released {age}\n\
+                                Run {command} to update";
+
+/// The default message for ASCII banners.
+const DEFAULT_ASCII_TEMPLATE: &str = "Update available {current} -> {latest}\n\
+                                      Released {age}\n\
+                                      Run {command} to update";
+
+/// Substitutes the placeholders in a template line.
+///
+/// Returns None if the line uses a placeholder without a value.
+fn fill_line(line: &str, values: &[(&str, Option<String>)]) -> Option<String> {
+    let mut filled = line.to_string();
+    for (name, value) in values {
+        let placeholder = format!("{{{}}}", name);
+        if filled.contains(&placeholder) {
+            filled = filled.replace(&placeholder, value.as_deref()?);
+        }
+    }
+    Some(filled)
+}
+
+/// Wraps a line at spaces to at most `max` characters, splitting longer words.
+fn wrap(line: &str, max: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in line.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let current_len = current.chars().count();
+        if current_len > 0 && current_len + 1 + word.len() > max {
+            lines.push(std::mem::take(&mut current));
+        }
+        while word.len() > max {
+            lines.push(word.drain(..max).collect());
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.extend(word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Colors the first occurrence of each highlighted value in a line.
+fn highlight(line: &str, highlights: &[(&str, &str)]) -> String {
+    let mut spans: Vec<(usize, usize, &str)> = Vec::new();
+    for &(value, color) in highlights {
+        if value.is_empty() {
+            continue;
+        }
+        let found = line.match_indices(value).find(|&(start, _)| {
+            let end = start + value.len();
+            spans.iter().all(|&(s, e, _)| end <= s || start >= e)
+        });
+        if let Some((start, _)) = found {
+            spans.push((start, start + value.len(), color));
+        }
+    }
+    spans.sort_unstable_by_key(|&(start, _, _)| start);
+
+    let mut out = String::new();
+    let mut cursor = 0;
+    for (start, end, color) in spans {
+        out.push_str(&line[cursor..start]);
+        out.push_str(color);
+        out.push_str(&line[start..end]);
+        out.push_str(RESET);
+        cursor = end;
+    }
+    out.push_str(&line[cursor..]);
+    out
+}
+
+/// Decides whether colors are allowed on a stream, following the `NO_COLOR`
+/// and `CLICOLOR` conventions.
+fn colors_allowed(is_terminal: bool, env: impl Fn(&str) -> Option<String>) -> bool {
+    let set = |name| env(name).is_some_and(|value| !value.is_empty() && value != "0");
+    if set("CLICOLOR_FORCE") {
+        return true;
+    }
+    if env("NO_COLOR").is_some_and(|value| !value.is_empty())
+        || env("CLICOLOR").as_deref() == Some("0")
+    {
+        return false;
+    }
+    is_terminal
+}
+
+/// Returns the width of the terminal stderr is connected to, falling back
+/// to `$COLUMNS` and then a default.
+fn terminal_width() -> usize {
+    terminal_size_of(std::io::stderr())
+        .map(|(Width(columns), _)| usize::from(columns))
+        .filter(|&columns| columns > 0)
+        .or_else(|| {
+            std::env::var("COLUMNS")
+                .ok()
+                .and_then(|columns| columns.trim().parse().ok())
+                .filter(|&columns| columns > 0)
+        })
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Returns the name used for an update kind in banners.
+fn kind_name(kind: UpdateKind) -> &'static str {
+    match kind {
+        UpdateKind::Major => "major",
+        UpdateKind::Minor => "minor",
+        UpdateKind::Patch => "patch",
+        UpdateKind::Prerelease => "prerelease",
+    }
+}
+
+/// Describes how long before `now` something happened, e.g. "3 days ago".
+fn relative_age(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - at).num_seconds().max(0);
+    let (count, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        86400..2_592_000 => (seconds / 86400, "day"),
+        2_592_000..31_536_000 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Release, UpdateStatus};
+    use crate::version::normalize_version;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn result(update_available: bool) -> VersionCheckResult {
+        VersionCheckResult {
+            update_available,
+            reason: UpdateStatus::UpdateAvailable,
+            current_version: normalize_version("1.0.0"),
+            latest_release: Some(Release {
+                tag_name: "v1.2.0".to_string(),
+                name: None,
+                body: None,
+                prerelease: false,
+                draft: false,
+                html_url: "https://github.com/owner/repo/releases/tag/v1.2.0".to_string(),
+                published_at: Some(now() - chrono::Duration::days(3)),
+            }),
+            out_of_scope_release: None,
+            update_kind: Some(UpdateKind::Minor),
+            versions_behind: 2,
+            stale: false,
+            cache_age: None,
+        }
+    }
+
+    fn banner() -> Banner {
+        Banner::new().color(ColorChoice::Never).width(80)
+    }
+
+    #[test]
+    fn test_renders_unicode_box() {
+        let rendered = banner()
+            .upgrade_command("cargo install my-cli")
+            .render_at(&result(true), now())
+            .unwrap();
+
+        let expected = [
+            "╭──────────────────────────────────────╮",
+            "│                                      │",
+            "│   Update available 1.0.0 → v1.2.0    │",
+            "│         Released 3 days ago          │",
+            "│  Run cargo install my-cli to update  │",
+            "│                                      │",
+            "╰──────────────────────────────────────╯",
+        ];
+        assert_eq!(rendered, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_renders_ascii_box_and_drops_lines_without_values() {
+        let rendered = banner()
+            .style(BoxStyle::Ascii)
+            .render_at(&result(true), now())
+            .unwrap();
+
+        assert!(rendered.starts_with("+---"));
+        assert!(rendered.contains("| "));
+        assert!(rendered.contains("1.0.0 -> v1.2.0"));
+        assert!(!rendered.contains("Run"));
+        assert!(rendered.is_ascii());
+    }
+
+    #[test]
+    fn test_custom_template() {
+        let rendered = banner()
+            .style(BoxStyle::None)
+            .template("{kind} update: {latest}\n{url}")
+            .render_at(&result(true), now())
+            .unwrap();
+
+        assert_eq!(
+            rendered,
+            "              minor update: v1.2.0\n\
+             https://github.com/owner/repo/releases/tag/v1.2.0"
+        );
+    }
+
+    #[test]
+    fn test_wraps_to_width() {
+        let rendered = banner()
+            .width(30)
+            .upgrade_command("cargo install --locked my-cli")
+            .render_at(&result(true), now())
+            .unwrap();
+
+        assert!(rendered.lines().all(|line| line.chars().count() <= 30));
+        assert!(rendered.contains("--locked"));
+    }
+
+    #[test]
+    fn test_colors() {
+        let rendered = banner()
+            .color(ColorChoice::Always)
+            .render_at(&result(true), now())
+            .unwrap();
+        assert!(rendered.contains(&format!("{}v1.2.0{}", GREEN, RESET)));
+
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(colors_allowed(true, env(&[])));
+        assert!(!colors_allowed(false, env(&[])));
+        assert!(!colors_allowed(true, env(&[("NO_COLOR", "1")])));
+        assert!(!colors_allowed(true, env(&[("CLICOLOR", "0")])));
+        assert!(colors_allowed(false, env(&[("CLICOLOR_FORCE", "1")])));
+    }
+
+    #[test]
+    fn test_no_banner_without_update() {
+        assert!(banner().render_at(&result(false), now()).is_none());
+    }
+
+    #[test]
+    fn test_relative_age() {
+        let ago = |seconds| relative_age(now() - chrono::Duration::seconds(seconds), now());
+        assert_eq!(ago(5), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(7200), "2 hours ago");
+        assert_eq!(ago(86400 * 45), "1 month ago");
+        assert_eq!(ago(86400 * 800), "2 years ago");
+    }
+}
@@ -0,0 +1,884 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::error::{ReleaseNotifierError, Result};
+use crate::types::{CommitCheckResult, FailureKind, Release};
+
+/// The current on-disk schema version. See [`CacheFile`].
+pub(crate) const CACHE_SCHEMA_VERSION: u32 = 2;
+
+/// File name of the cache inside the default cache directory.
+pub(crate) const DEFAULT_CACHE_FILE_NAME: &str = "cache.json";
+
+/// How often to retry taking the cache lock while another process holds it.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for the cache lock before refreshing without it.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Backoff after the first failed request; doubled for each further failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Upper bound on the backoff between failed requests.
+const MAX_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Distinguishes temp files written concurrently by the same process.
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the default directory for relnotify's cache.
+///
+/// This is `$XDG_CACHE_HOME/relnotify`, falling back to `~/.cache/relnotify`
+/// (or `%LOCALAPPDATA%\relnotify` on Windows). Returns None if neither can be
+/// determined.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    cache_dir_from(|name| std::env::var_os(name))
+}
+
+/// Works out the default cache directory from the environment variables `env` returns.
+pub(crate) fn cache_dir_from(env: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    let from_env = |name: &str| env(name).map(PathBuf::from).filter(|p| p.is_absolute());
+
+    let base = from_env("XDG_CACHE_HOME")
+        .or_else(|| from_env("HOME").map(|home| home.join(".cache")))
+        .or_else(|| from_env("LOCALAPPDATA"))?;
+
+    Some(base.join("relnotify"))
+}
+
+/// The kind of data a cache entry holds for a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CacheSource {
+    /// The list of releases.
+    Releases,
+    /// The release GitHub marks as "Latest".
+    DesignatedLatest,
+    /// A comparison between a commit and a branch.
+    Comparison { sha: String, branch: String },
+    /// The release a version was tagged as, looked up by tag.
+    TagLookup { version: String },
+}
+
+impl fmt::Display for CacheSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Releases => write!(f, "releases"),
+            Self::DesignatedLatest => write!(f, "latest"),
+            Self::Comparison { sha, branch } => write!(f, "compare/{}...{}", sha, branch),
+            Self::TagLookup { version } => write!(f, "tags/{}", version),
+        }
+    }
+}
+
+/// Identifies a cache entry by API base URL, repository and source type, so
+/// notifiers for different repositories can share one cache file safely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheKey {
+    pub base_url: String,
+    pub repo: String,
+    pub source: CacheSource,
+}
+
+impl CacheKey {
+    /// Returns the prefix shared by all keys for a repository.
+    pub(crate) fn repo_prefix(base_url: &str, repo: &str) -> String {
+        format!("{}|{}|", base_url, repo)
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            Self::repo_prefix(&self.base_url, &self.repo),
+            self.source
+        )
+    }
+}
+
+/// A cached value with the time it was fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    pub value: CacheValue,
+    /// When the value was fetched, in milliseconds since the Unix epoch.
+    pub fetched_at: i64,
+    /// The ETag GitHub returned with the value, used to revalidate it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+/// The data held by a cache entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub(crate) enum CacheValue {
+    Releases(Vec<Release>),
+    DesignatedLatest(Option<Release>),
+    Comparison(CommitCheckResult),
+    TagLookup(Option<Release>),
+}
+
+impl CacheValue {
+    /// Returns true if this value holds the kind of data `source` refers to.
+    pub(crate) fn is_for(&self, source: &CacheSource) -> bool {
+        matches!(
+            (self, source),
+            (Self::Releases(_), CacheSource::Releases)
+                | (Self::DesignatedLatest(_), CacheSource::DesignatedLatest)
+                | (Self::Comparison(_), CacheSource::Comparison { .. })
+                | (Self::TagLookup(_), CacheSource::TagLookup { .. })
+        )
+    }
+}
+
+/// A failed request for a cache key, used to back off before retrying.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FailureRecord {
+    pub kind: FailureKind,
+    /// When the latest failure happened, in milliseconds since the Unix epoch.
+    pub failed_at: i64,
+    /// The number of failures in a row.
+    pub consecutive: u32,
+}
+
+impl FailureRecord {
+    /// Returns when the next request may be made, in milliseconds since the Unix epoch.
+    pub(crate) fn retry_at(&self) -> i64 {
+        self.failed_at + backoff_delay(self.consecutive).as_millis() as i64
+    }
+}
+
+/// Which update was last shown to the user, and when they asked not to be reminded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct NotificationState {
+    /// The tag of the release last shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_version: Option<String>,
+    /// When it was last shown, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_notified_at: Option<i64>,
+    /// Notifications are suppressed until this time, in milliseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<i64>,
+    /// A version the user never wants to be notified about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dismissed_version: Option<String>,
+}
+
+impl NotificationState {
+    /// Returns true if nothing has been recorded.
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Returns the backoff after the given number of consecutive failures.
+fn backoff_delay(consecutive: u32) -> Duration {
+    let doublings = consecutive.saturating_sub(1).min(31);
+    INITIAL_BACKOFF
+        .saturating_mul(1 << doublings)
+        .min(MAX_BACKOFF)
+}
+
+/// One repository's cached data: fetched releases and comparisons, the
+/// failure records used for backoff, and which updates were shown to the user.
+///
+/// The contents are opaque; a [`CacheStore`](crate::CacheStore) only needs to
+/// keep the record as-is or round-trip it through serde. Deserializing drops
+/// individual entries that can't be parsed rather than failing.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepoCache {
+    /// Entries keyed by the string form of their [`CacheKey`].
+    pub(crate) entries: HashMap<String, CacheEntry>,
+    /// Failed requests, keyed like the entries they would have filled.
+    pub(crate) failures: HashMap<String, FailureRecord>,
+    /// Which update was last shown to the user, and any snooze or dismissal.
+    #[serde(skip_serializing_if = "NotificationState::is_empty")]
+    pub(crate) notification: NotificationState,
+}
+
+impl<'de> Deserialize<'de> for RepoCache {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        let entries = raw.get("entries").and_then(parse_map).unwrap_or_default();
+        let failures = raw.get("failures").and_then(parse_map).unwrap_or_default();
+        let notification = raw
+            .get("notification")
+            .and_then(|value| NotificationState::deserialize(value).ok())
+            .unwrap_or_default();
+        Ok(Self {
+            entries: entries.into_iter().collect(),
+            failures: failures.into_iter().collect(),
+            notification,
+        })
+    }
+}
+
+impl RepoCache {
+    /// Returns true if the record holds no entries, failure records or notification state.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.failures.is_empty() && self.notification.is_empty()
+    }
+
+    /// Returns the failure record for a key if it still blocks requests at `now`.
+    ///
+    /// A failure is ignored once the key has been fetched successfully after it.
+    pub(crate) fn pending_failure(&self, key: &str, now: i64) -> Option<&FailureRecord> {
+        let failure = self.failures.get(key)?;
+        let superseded = self
+            .entries
+            .get(key)
+            .is_some_and(|entry| entry.fetched_at > failure.failed_at);
+        (!superseded && failure.retry_at() > now).then_some(failure)
+    }
+}
+
+/// Internal cache file structure for disk persistence.
+///
+/// Schema versions:
+/// - 0: a single repository's data (`releases`, `last_fetch_time`, ...), unversioned
+/// - 1: entries keyed by [`CacheKey`], unversioned
+/// - 2: version 1 with an explicit `schema_version`
+#[derive(Debug, Serialize)]
+pub(crate) struct CacheFile {
+    /// The schema version the file was written with. Files from older versions
+    /// are migrated on load; files from newer versions are never written.
+    pub schema_version: u32,
+    /// Entries keyed by the string form of their [`CacheKey`].
+    pub entries: BTreeMap<String, CacheEntry>,
+    /// Failed requests, keyed like the entries they would have filled.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub failures: BTreeMap<String, FailureRecord>,
+    /// Notification state, keyed by [`CacheFile::repo_id`].
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub notifications: BTreeMap<String, NotificationState>,
+}
+
+impl Default for CacheFile {
+    fn default() -> Self {
+        Self {
+            schema_version: CACHE_SCHEMA_VERSION,
+            entries: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            notifications: BTreeMap::new(),
+        }
+    }
+}
+
+impl CacheFile {
+    /// Loads a cache file, returning None if it is missing or unreadable.
+    ///
+    /// Files written by a newer version of this library are always returned,
+    /// read-only, even if their entries can't be parsed. Legacy single-repository files are migrated into the given repository's entries.
+    pub(crate) fn load(path: impl AsRef<Path>, base_url: &str, repo: &str) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        Self::parse(&content, base_url, repo)
+    }
+
+    /// Parses a cache file, migrating older schema versions to the current one.
+    ///
+    /// Entries that fail to parse are dropped individually rather than
+    /// discarding the whole file.
+    fn parse(content: &str, base_url: &str, repo: &str) -> Option<Self> {
+        let raw: Value = serde_json::from_str(content).ok()?;
+        let schema_version = match raw.get("schema_version") {
+            Some(version) => u32::try_from(version.as_u64()?).ok()?,
+            None if raw.get("entries").is_some() => 1,
+            None => 0,
+        };
+
+        let entries = match schema_version {
+            0 => LegacyCacheData::deserialize(&raw)
+                .ok()?
+                .migrate(base_url, repo),
+            // A newer layout may not parse; keep what can be read, but never write it
+            v if v > CACHE_SCHEMA_VERSION => {
+                raw.get("entries").and_then(parse_map).unwrap_or_default()
+            }
+            _ => parse_map(raw.get("entries")?)?,
+        };
+        let failures = raw.get("failures").and_then(parse_map).unwrap_or_default();
+        let notifications = raw
+            .get("notifications")
+            .and_then(parse_map)
+            .unwrap_or_default();
+
+        Some(Self {
+            // Older files are upgraded when saved; newer ones keep their version
+            schema_version: schema_version.max(CACHE_SCHEMA_VERSION),
+            entries,
+            failures,
+            notifications,
+        })
+    }
+
+    /// Returns true if the file was written by a newer version of this library
+    /// and must not be overwritten.
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.schema_version > CACHE_SCHEMA_VERSION
+    }
+
+    /// Saves the cache file.
+    ///
+    /// The content is written to a temp file next to the target and renamed
+    /// over it, so readers never see a partially written file.
+    pub(crate) fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        self.check_writable()?;
+        let content = serde_json::to_string(self)?;
+        let temp_path = temp_path_for(path);
+
+        let written = File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(content.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| fs::rename(&temp_path, path));
+
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// Refuses to overwrite a file written by a newer version of this library.
+    fn check_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(ReleaseNotifierError::UnsupportedCacheSchema(
+                self.schema_version,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns true if the file holds no entries, failure records or notification state.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.failures.is_empty() && self.notifications.is_empty()
+    }
+
+    /// Returns the key of a repository's notification state.
+    fn repo_id(base_url: &str, repo: &str) -> String {
+        format!("{}|{}", base_url, repo)
+    }
+
+    /// Returns a copy of the data belonging to a repository.
+    pub(crate) fn repo_cache(&self, base_url: &str, repo: &str) -> RepoCache {
+        let prefix = CacheKey::repo_prefix(base_url, repo);
+        RepoCache {
+            entries: scoped(&self.entries, &prefix),
+            failures: scoped(&self.failures, &prefix),
+            notification: self
+                .notifications
+                .get(&Self::repo_id(base_url, repo))
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Removes the data belonging to a repository.
+    pub(crate) fn remove_repo(&mut self, base_url: &str, repo: &str) {
+        let prefix = CacheKey::repo_prefix(base_url, repo);
+        self.entries.retain(|key, _| !key.starts_with(&prefix));
+        self.failures.retain(|key, _| !key.starts_with(&prefix));
+        self.notifications.remove(&Self::repo_id(base_url, repo));
+    }
+
+    /// Replaces a repository's data.
+    pub(crate) fn replace_repo(&mut self, base_url: &str, repo: &str, cache: RepoCache) {
+        self.remove_repo(base_url, repo);
+        self.entries.extend(cache.entries);
+        self.failures.extend(cache.failures);
+        if !cache.notification.is_empty() {
+            self.notifications
+                .insert(Self::repo_id(base_url, repo), cache.notification);
+        }
+    }
+}
+
+/// Parses a JSON object into a map, dropping values that fail to parse.
+fn parse_map<T: for<'de> Deserialize<'de>>(raw: &Value) -> Option<BTreeMap<String, T>> {
+    let map = raw
+        .as_object()?
+        .iter()
+        .filter_map(|(key, value)| Some((key.clone(), T::deserialize(value).ok()?)))
+        .collect();
+    Some(map)
+}
+
+/// Copies the values whose keys start with `prefix`.
+fn scoped<T: Clone>(map: &BTreeMap<String, T>, prefix: &str) -> HashMap<String, T> {
+    map.iter()
+        .filter(|(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// The unversioned, single-repository cache format (schema version 0).
+#[derive(Deserialize)]
+struct LegacyCacheData {
+    releases: Vec<Release>,
+    last_fetch_time: i64,
+    #[serde(default)]
+    designated_latest: Option<Release>,
+    #[serde(default)]
+    designated_latest_fetch_time: Option<i64>,
+    #[serde(default)]
+    comparisons: HashMap<String, LegacyComparison>,
+}
+
+/// A commit comparison in the legacy format, keyed by "sha...branch".
+#[derive(Deserialize)]
+struct LegacyComparison {
+    result: CommitCheckResult,
+    fetch_time: i64,
+}
+
+impl LegacyCacheData {
+    /// Converts the legacy data into keyed entries for a repository.
+    ///
+    /// Legacy files didn't record their repository, so data is only carried over
+    /// if its URLs show that it belongs to `repo`.
+    fn migrate(self, base_url: &str, repo: &str) -> BTreeMap<String, CacheEntry> {
+        let key = |source| {
+            CacheKey {
+                base_url: base_url.to_string(),
+                repo: repo.to_string(),
+                source,
+            }
+            .to_string()
+        };
+        let mut entries = BTreeMap::new();
+
+        if !self.releases.is_empty()
+            && self
+                .releases
+                .iter()
+                .all(|r| url_is_in_repo(&r.html_url, repo))
+        {
+            entries.insert(
+                key(CacheSource::Releases),
+                CacheEntry {
+                    value: CacheValue::Releases(self.releases),
+                    fetched_at: self.last_fetch_time,
+                    etag: None,
+                },
+            );
+        }
+
+        if let Some(fetched_at) = self.designated_latest_fetch_time {
+            let release = self.designated_latest;
+            if release
+                .as_ref()
+                .is_none_or(|r| url_is_in_repo(&r.html_url, repo))
+            {
+                entries.insert(
+                    key(CacheSource::DesignatedLatest),
+                    CacheEntry {
+                        value: CacheValue::DesignatedLatest(release),
+                        fetched_at,
+                        etag: None,
+                    },
+                );
+            }
+        }
+
+        for (pair, comparison) in self.comparisons {
+            let Some((sha, branch)) = pair.split_once("...") else {
+                continue;
+            };
+            if url_is_in_repo(&comparison.result.compare_url, repo) {
+                let source = CacheSource::Comparison {
+                    sha: sha.to_string(),
+                    branch: branch.to_string(),
+                };
+                entries.insert(
+                    key(source),
+                    CacheEntry {
+                        value: CacheValue::Comparison(comparison.result),
+                        fetched_at: comparison.fetch_time,
+                        etag: None,
+                    },
+                );
+            }
+        }
+
+        entries
+    }
+}
+
+/// Returns true if a GitHub web URL points into the given "owner/repo".
+fn url_is_in_repo(url: &str, repo: &str) -> bool {
+    url.to_ascii_lowercase()
+        .contains(&format!("/{}/", repo.to_ascii_lowercase()))
+}
+
+/// Returns a unique temp file path next to a cache file.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    PathBuf::from(name)
+}
+
+/// An advisory lock on a cache file, held while it is refreshed.
+///
+/// The lock lives in a sibling `<cache>.lock` file so the cache itself can be
+/// replaced atomically. It is released when dropped.
+#[derive(Debug)]
+pub(crate) struct CacheLock {
+    _file: File,
+}
+
+impl CacheLock {
+    /// Takes the lock for a cache file, blocking while another process holds it.
+    ///
+    /// Returns None if the lock file can't be opened or the lock isn't released
+    /// within the timeout; callers then proceed without it.
+    pub(crate) fn acquire(path: &Path) -> Option<Self> {
+        let file = Self::open(path)?;
+
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Some(Self { _file: file }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Takes the lock for a cache file if no other process holds it.
+    ///
+    /// Returns None if the lock is held or the lock file can't be opened.
+    pub(crate) fn try_acquire(path: &Path) -> Option<Self> {
+        let file = Self::open(path)?;
+        file.try_lock().ok()?;
+        Some(Self { _file: file })
+    }
+
+    /// Opens the `<path>.lock` file, creating it if needed.
+    fn open(path: &Path) -> Option<File> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_dir_from_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        let dir = cache_dir_from(env(&[("XDG_CACHE_HOME", "/xdg"), ("HOME", "/home/user")]));
+        assert_eq!(dir, Some(PathBuf::from("/xdg/relnotify")));
+        let dir = cache_dir_from(env(&[
+            ("XDG_CACHE_HOME", "relative"),
+            ("HOME", "/home/user"),
+        ]));
+        assert_eq!(dir, Some(PathBuf::from("/home/user/.cache/relnotify")));
+        assert_eq!(cache_dir_from(env(&[])), None);
+    }
+
+    fn key(repo: &str, source: CacheSource) -> CacheKey {
+        CacheKey {
+            base_url: "https://api.github.com".to_string(),
+            repo: repo.to_string(),
+            source,
+        }
+    }
+
+    fn entry() -> CacheEntry {
+        CacheEntry {
+            value: CacheValue::Releases(Vec::new()),
+            fetched_at: 0,
+            etag: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_key_format() {
+        let comparison = CacheSource::Comparison {
+            sha: "abc1234".to_string(),
+            branch: "main".to_string(),
+        };
+
+        assert_eq!(
+            key("owner/repo", CacheSource::Releases).to_string(),
+            "https://api.github.com|owner/repo|releases"
+        );
+        assert_eq!(
+            key("owner/repo", comparison).to_string(),
+            "https://api.github.com|owner/repo|compare/abc1234...main"
+        );
+    }
+
+    #[test]
+    fn test_save_replaces_file_without_leaving_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let path = path.to_str().unwrap();
+        fs::write(path, "stale").unwrap();
+
+        let mut file = CacheFile::default();
+        file.entries.insert(
+            key("owner/repo", CacheSource::Releases).to_string(),
+            entry(),
+        );
+        file.save(path).unwrap();
+
+        let loaded = CacheFile::load(path, "https://api.github.com", "owner/repo").unwrap();
+        assert_eq!(loaded.entries.len(), 1);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    fn legacy_cache_json(repo: &str) -> String {
+        serde_json::json!({
+            "releases": [{
+                "tag_name": "v1.0.0",
+                "name": null,
+                "body": null,
+                "prerelease": false,
+                "draft": false,
+                "html_url": format!("https://github.com/{}/releases/tag/v1.0.0", repo),
+                "published_at": "2024-01-01T00:00:00Z"
+            }],
+            "last_fetch_time": 1700000000000_i64,
+            "comparisons": {
+                "abc1234...main": {
+                    "result": {
+                        "update_available": true,
+                        "commits_behind": 2,
+                        "latest_commit_date": null,
+                        "compare_url": format!("https://github.com/{}/compare/abc1234...main", repo)
+                    },
+                    "fetch_time": 1700000000000_i64
+                }
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_migrates_legacy_cache() {
+        let content = legacy_cache_json("owner/repo");
+        let file = CacheFile::parse(&content, "https://api.github.com", "owner/repo").unwrap();
+
+        assert_eq!(file.schema_version, CACHE_SCHEMA_VERSION);
+        assert!(!file.is_read_only());
+
+        let releases = &file.entries[&key("owner/repo", CacheSource::Releases).to_string()];
+        assert_eq!(releases.fetched_at, 1700000000000);
+        assert!(matches!(&releases.value, CacheValue::Releases(r) if r.len() == 1));
+
+        let comparison = CacheSource::Comparison {
+            sha: "abc1234".to_string(),
+            branch: "main".to_string(),
+        };
+        assert!(file
+            .entries
+            .contains_key(&key("owner/repo", comparison).to_string()));
+    }
+
+    #[test]
+    fn test_legacy_cache_for_other_repo_is_not_migrated() {
+        let content = legacy_cache_json("owner/other");
+        let file = CacheFile::parse(&content, "https://api.github.com", "owner/repo").unwrap();
+
+        assert!(file.entries.is_empty());
+    }
+
+    #[test]
+    fn test_migrates_unversioned_keyed_cache() {
+        let mut entries = BTreeMap::new();
+        entries.insert(
+            key("owner/repo", CacheSource::Releases).to_string(),
+            entry(),
+        );
+        let content = serde_json::json!({ "entries": entries }).to_string();
+
+        let file = CacheFile::parse(&content, "https://api.github.com", "owner/repo").unwrap();
+        assert_eq!(file.schema_version, CACHE_SCHEMA_VERSION);
+        assert_eq!(file.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_unparseable_entries_are_dropped_individually() {
+        let content = serde_json::json!({
+            "schema_version": CACHE_SCHEMA_VERSION,
+            "entries": {
+                key("owner/repo", CacheSource::Releases).to_string(): entry(),
+                key("owner/repo", CacheSource::DesignatedLatest).to_string(): { "kind": "unknown" },
+            }
+        })
+        .to_string();
+
+        let file = CacheFile::parse(&content, "https://api.github.com", "owner/repo").unwrap();
+        assert_eq!(file.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_newer_schema_is_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let path = path.to_str().unwrap();
+
+        let content = serde_json::json!({
+            "schema_version": CACHE_SCHEMA_VERSION + 1,
+            "entries": { key("owner/repo", CacheSource::Releases).to_string(): entry() }
+        })
+        .to_string();
+        fs::write(path, &content).unwrap();
+
+        let file = CacheFile::load(path, "https://api.github.com", "owner/repo").unwrap();
+        assert!(file.is_read_only());
+        assert_eq!(file.entries.len(), 1);
+
+        assert!(matches!(
+            file.save(path),
+            Err(ReleaseNotifierError::UnsupportedCacheSchema(v)) if v == CACHE_SCHEMA_VERSION + 1
+        ));
+        assert_eq!(fs::read_to_string(path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_newer_schema_with_unknown_layout_is_read_only() {
+        let content = serde_json::json!({
+            "schema_version": CACHE_SCHEMA_VERSION + 1,
+            "records": { "owner/repo": { "releases": [] } }
+        })
+        .to_string();
+
+        let file = CacheFile::parse(&content, "https://api.github.com", "owner/repo").unwrap();
+        assert!(file.is_read_only());
+        assert!(file.is_empty());
+    }
+
+    #[test]
+    fn test_cache_lock_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let lock = CacheLock::acquire(&path).unwrap();
+        let path = path.to_str().unwrap();
+
+        let contender = File::open(format!("{}.lock", path)).unwrap();
+        assert!(matches!(
+            contender.try_lock(),
+            Err(TryLockError::WouldBlock)
+        ));
+
+        drop(lock);
+        assert!(contender.try_lock().is_ok());
+    }
+
+    #[test]
+    fn test_entries_are_scoped_to_repo() {
+        let mut file = CacheFile::default();
+        for source in [CacheSource::Releases, CacheSource::DesignatedLatest] {
+            file.entries
+                .insert(key("owner/repo", source.clone()).to_string(), entry());
+            file.entries
+                .insert(key("owner/repo-two", source).to_string(), entry());
+        }
+
+        file.failures.insert(
+            key("owner/repo", CacheSource::Releases).to_string(),
+            failure(0, 1),
+        );
+
+        let repo_cache = file.repo_cache("https://api.github.com", "owner/repo");
+        assert_eq!(repo_cache.entries.len(), 2);
+        assert_eq!(repo_cache.failures.len(), 1);
+
+        file.remove_repo("https://api.github.com", "owner/repo");
+        assert_eq!(file.entries.len(), 2);
+        assert!(file.entries.keys().all(|k| k.contains("owner/repo-two")));
+        assert!(file.failures.is_empty());
+    }
+
+    #[test]
+    fn test_notification_state_is_scoped_to_repo() {
+        let base_url = "https://api.github.com";
+        let mut record = RepoCache::default();
+        record.notification.dismissed_version = Some("2.0.0".to_string());
+        assert!(!record.is_empty());
+
+        let mut file = CacheFile::default();
+        file.replace_repo(base_url, "owner/repo", record);
+        let content = serde_json::to_string(&file).unwrap();
+        let file = CacheFile::parse(&content, base_url, "owner/repo").unwrap();
+
+        let repo_cache = file.repo_cache(base_url, "owner/repo");
+        assert_eq!(
+            repo_cache.notification.dismissed_version.as_deref(),
+            Some("2.0.0")
+        );
+        assert!(file.repo_cache(base_url, "owner/repo-two").is_empty());
+    }
+
+    fn failure(failed_at: i64, consecutive: u32) -> FailureRecord {
+        FailureRecord {
+            kind: FailureKind::ServerError,
+            failed_at,
+            consecutive,
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_limit() {
+        assert_eq!(backoff_delay(1), Duration::from_secs(60));
+        assert_eq!(backoff_delay(2), Duration::from_secs(120));
+        assert_eq!(backoff_delay(4), Duration::from_secs(480));
+        assert_eq!(backoff_delay(20), MAX_BACKOFF);
+        assert_eq!(backoff_delay(u32::MAX), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_pending_failure() {
+        let releases = key("owner/repo", CacheSource::Releases).to_string();
+        let mut cache = RepoCache::default();
+        cache.failures.insert(releases.clone(), failure(1_000, 1));
+
+        assert!(cache.pending_failure(&releases, 1_000 + 59_999).is_some());
+        assert!(cache.pending_failure(&releases, 1_000 + 60_000).is_none());
+
+        // A later successful fetch supersedes the failure
+        cache.entries.insert(
+            releases.clone(),
+            CacheEntry {
+                value: CacheValue::Releases(Vec::new()),
+                fetched_at: 2_000,
+                etag: None,
+            },
+        );
+        assert!(cache.pending_failure(&releases, 2_000).is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::types::Release;
+
+/// Marker appended when a changelog is cut short to fit `max_length`.
+const TRUNCATION_MARKER: &str = "\n\n… (truncated)";
+
+/// Options for building an aggregated changelog.
+#[derive(Debug, Clone, Default)]
+pub struct ChangelogOptions {
+    /// Maximum length of the changelog in characters, including the truncation marker.
+    pub max_length: Option<usize>,
+    /// If true, prereleases are left out of the changelog.
+    pub skip_prereleases: bool,
+}
+
+impl ChangelogOptions {
+    /// Creates options with no length limit that include prereleases.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum changelog length in characters.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets whether prereleases are left out of the changelog.
+    pub fn skip_prereleases(mut self, skip: bool) -> Self {
+        self.skip_prereleases = skip;
+        self
+    }
+}
+
+/// Merges the release notes of several releases into a single changelog.
+///
+/// Each release gets a Markdown header with its tag and publish date, and
+/// releases are rendered in the order given (newest first when used with
+/// `ReleaseNotifier::releases_since`). Draft releases are always skipped.
+pub fn build_changelog(releases: &[Release], options: &ChangelogOptions) -> String {
+    let sections: Vec<String> = releases
+        .iter()
+        .filter(|r| !r.draft)
+        .filter(|r| !(options.skip_prereleases && r.prerelease))
+        .map(render_section)
+        .collect();
+
+    let changelog = sections.join("\n\n");
+
+    match options.max_length {
+        Some(max_length) => truncate(changelog, max_length),
+        None => changelog,
+    }
+}
+
+fn render_section(release: &Release) -> String {
+    let mut section = format!("## {}", release.tag_name);
+    if let Some(published_at) = release.published_at {
+        section.push_str(&format!(" ({})", published_at.format("%Y-%m-%d")));
+    }
+
+    let body = release.body.as_deref().map(str::trim).unwrap_or_default();
+    if !body.is_empty() {
+        section.push_str("\n\n");
+        section.push_str(body);
+    }
+
+    section
+}
+
+fn truncate(changelog: String, max_length: usize) -> String {
+    if changelog.chars().count() <= max_length {
+        return changelog;
+    }
+
+    let marker_length = TRUNCATION_MARKER.chars().count();
+    if max_length <= marker_length {
+        return changelog.chars().take(max_length).collect();
+    }
+
+    let mut truncated: String = changelog.chars().take(max_length - marker_length).collect();
+    truncated.push_str(TRUNCATION_MARKER);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::release;
+
+    #[test]
+    fn test_build_changelog_headers() {
+        let releases = [
+            release("v2.0.0", "Breaking change", false),
+            release("v1.1.0", "New feature\n", false),
+        ];
+
+        let changelog = build_changelog(&releases, &ChangelogOptions::new());
+        assert_eq!(
+            changelog,
+            "## v2.0.0 (2024-03-15)\n\nBreaking change\n\n## v1.1.0 (2024-03-15)\n\nNew feature"
+        );
+    }
+
+    #[test]
+    fn test_build_changelog_skips_prereleases() {
+        let releases = [
+            release("v2.0.0-beta.1", "Beta", true),
+            release("v1.1.0", "Stable", false),
+        ];
+
+        let changelog = build_changelog(&releases, &ChangelogOptions::new().skip_prereleases(true));
+        assert!(!changelog.contains("v2.0.0-beta.1"));
+        assert!(changelog.contains("v1.1.0"));
+    }
+
+    #[test]
+    fn test_build_changelog_max_length() {
+        let releases = [release("v2.0.0", &"x".repeat(200), false)];
+
+        let changelog = build_changelog(&releases, &ChangelogOptions::new().max_length(50));
+        assert_eq!(changelog.chars().count(), 50);
+        assert!(changelog.ends_with(TRUNCATION_MARKER));
+    }
+}
@@ -0,0 +1,167 @@
+use regex::Regex;
+
+use crate::error::{ReleaseNotifierError, Result};
+use crate::types::{Channel, ChannelSource, Release};
+use crate::version::{normalize_tag, parse_version, TagMatcher};
+
+/// Compiled form of a [`ChannelSource`], used to work out each release's channel.
+#[derive(Debug, Clone)]
+pub(crate) enum ChannelMatcher {
+    Prerelease,
+    TagPatterns(Vec<(Channel, Regex)>),
+    BodyMarker(String),
+}
+
+impl ChannelMatcher {
+    /// Compiles a channel source, validating tag patterns up front.
+    pub(crate) fn new(source: &ChannelSource) -> Result<Self> {
+        match source {
+            ChannelSource::Prerelease => Ok(Self::Prerelease),
+            ChannelSource::TagPatterns(patterns) => patterns
+                .iter()
+                .map(|(channel, pattern)| {
+                    Regex::new(pattern)
+                        .map(|regex| (*channel, regex))
+                        .map_err(|_| ReleaseNotifierError::InvalidChannelPattern(pattern.clone()))
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(Self::TagPatterns),
+            ChannelSource::BodyMarker(marker) => Ok(Self::BodyMarker(marker.to_lowercase())),
+        }
+    }
+
+    /// Works out the channel a release was published on.
+    ///
+    /// If the configured source doesn't identify the channel, the semver
+    /// prerelease identifiers of the tag are used. Prereleases whose channel
+    /// still can't be identified are treated as nightlies, so they are never
+    /// offered to users on a more stable channel.
+    pub(crate) fn channel_of(
+        &self,
+        release: &Release,
+        tag_matcher: Option<&TagMatcher>,
+    ) -> Channel {
+        let from_source = match self {
+            Self::Prerelease => None,
+            Self::TagPatterns(patterns) => patterns
+                .iter()
+                .find(|(_, regex)| regex.is_match(&release.tag_name))
+                .map(|(channel, _)| *channel),
+            Self::BodyMarker(marker) => release
+                .body
+                .as_deref()
+                .and_then(|body| channel_from_body(body, marker)),
+        };
+
+        from_source
+            .or_else(|| channel_from_tag(&release.tag_name, tag_matcher))
+            .unwrap_or(if release.prerelease {
+                Channel::Nightly
+            } else {
+                Channel::Stable
+            })
+    }
+}
+
+/// Works out a channel from the semver prerelease identifiers of a tag or version.
+///
+/// Returns `Stable` for versions without prerelease identifiers, and None if
+/// the version can't be parsed or its identifiers aren't recognized.
+pub(crate) fn channel_from_tag(tag: &str, tag_matcher: Option<&TagMatcher>) -> Option<Channel> {
+    let version = parse_version(normalize_tag(tag, tag_matcher))?;
+    if version.pre.is_empty() {
+        return Some(Channel::Stable);
+    }
+
+    // "beta.1", "beta1" and "beta-1" all identify the beta channel
+    let identifier = version
+        .pre
+        .as_str()
+        .split(['.', '-'])
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(|c: char| c.is_ascii_digit());
+
+    Channel::from_identifier(identifier)
+}
+
+/// Finds a channel marker such as `channel: beta` in a release body.
+fn channel_from_body(body: &str, marker: &str) -> Option<Channel> {
+    body.lines().find_map(|line| {
+        let line = line.to_lowercase();
+        let start = line.find(marker)? + marker.len();
+        let identifier = line[start..]
+            .trim_start()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()?;
+        Channel::from_identifier(identifier)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::release;
+
+    #[test]
+    fn test_channel_from_prerelease_identifiers() {
+        let matcher = ChannelMatcher::new(&ChannelSource::Prerelease).unwrap();
+        let channel_of =
+            |tag: &str, prerelease: bool| matcher.channel_of(&release(tag, "", prerelease), None);
+
+        assert_eq!(channel_of("v2.0.0", false), Channel::Stable);
+        assert_eq!(channel_of("v2.0.0-rc.1", true), Channel::Rc);
+        assert_eq!(channel_of("v2.0.0-beta2", true), Channel::Beta);
+        assert_eq!(channel_of("v2.0.0-alpha.3", true), Channel::Alpha);
+        assert_eq!(
+            channel_of("v2.0.0-nightly.20241010", true),
+            Channel::Nightly
+        );
+        // Unrecognized prereleases are never offered to more stable channels
+        assert_eq!(channel_of("v2.0.0-foo", true), Channel::Nightly);
+        assert_eq!(channel_of("latest-build", false), Channel::Stable);
+    }
+
+    #[test]
+    fn test_channel_from_tag_patterns() {
+        let matcher = ChannelMatcher::new(&ChannelSource::TagPatterns(vec![
+            (Channel::Nightly, "^nightly-".to_string()),
+            (Channel::Beta, "^preview-".to_string()),
+        ]))
+        .unwrap();
+
+        let channel_of = |tag: &str| matcher.channel_of(&release(tag, "", true), None);
+        assert_eq!(channel_of("nightly-2024-10-10"), Channel::Nightly);
+        assert_eq!(channel_of("preview-5"), Channel::Beta);
+        // Falls back to prerelease identifiers
+        assert_eq!(channel_of("v2.0.0-rc.1"), Channel::Rc);
+    }
+
+    #[test]
+    fn test_channel_from_body_marker() {
+        let matcher =
+            ChannelMatcher::new(&ChannelSource::BodyMarker("Channel:".to_string())).unwrap();
+
+        let body = "Fixes a crash.\n\n<!-- channel: beta -->";
+        assert_eq!(
+            matcher.channel_of(&release("build-1234", body, true), None),
+            Channel::Beta
+        );
+        assert_eq!(
+            matcher.channel_of(&release("build-1235", "No marker", true), None),
+            Channel::Nightly
+        );
+    }
+
+    #[test]
+    fn test_invalid_channel_pattern() {
+        let result = ChannelMatcher::new(&ChannelSource::TagPatterns(vec![(
+            Channel::Beta,
+            "beta-(".to_string(),
+        )]));
+        assert!(matches!(
+            result,
+            Err(ReleaseNotifierError::InvalidChannelPattern(_))
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// A source of the current time.
+///
+/// Every time-based decision a notifier makes (cache expiry, failure backoff
+/// and the like) reads the time from its clock, so tests can control it with
+/// [`ManualClock`](crate::testing::ManualClock) instead of sleeping.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
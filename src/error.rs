@@ -1,5 +1,8 @@
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
+use crate::types::FailureKind;
+
 /// Errors that can occur when using the ReleaseNotifier.
 #[derive(Error, Debug)]
 pub enum ReleaseNotifierError {
@@ -15,6 +18,19 @@ pub enum ReleaseNotifierError {
     #[error("GitHub API error: {status} - {message}")]
     ApiError { status: u16, message: String },
 
+    /// Requests are paused after repeated failures; see
+    /// [`ReleaseNotifier::backoff_status`](crate::ReleaseNotifier::backoff_status).
+    #[error("Update checks paused after repeated failures until {until}")]
+    BackingOff {
+        kind: FailureKind,
+        until: DateTime<Utc>,
+    },
+
+    /// Update checks are turned off, so GitHub wasn't contacted; see
+    /// [`ReleaseNotifier::is_disabled`](crate::ReleaseNotifier::is_disabled).
+    #[error("Update checks are turned off")]
+    Disabled,
+
     /// Invalid repository format.
     #[error("Invalid repository format: expected 'owner/repo', got '{0}'")]
     InvalidRepo(String),
@@ -27,6 +43,30 @@ pub enum ReleaseNotifierError {
     #[error("Invalid cache file path: parent directory does not exist for '{0}'")]
     InvalidCacheFilePath(String),
 
+    /// The default cache location was requested but couldn't be determined.
+    #[error("Could not determine the default cache location: set XDG_CACHE_HOME or HOME")]
+    NoDefaultCacheLocation,
+
+    /// The cache file was written by a newer version of this library and is left untouched.
+    #[error("Cache file uses schema version {0}, which is newer than this library supports")]
+    UnsupportedCacheSchema(u32),
+
+    /// A cache store failed to read or write its backing storage.
+    #[error("Cache store error: {0}")]
+    CacheStore(Box<dyn std::error::Error + Send + Sync>),
+
+    /// Invalid tag pattern (malformed regex or no capture group for the version).
+    #[error("Invalid tag pattern: '{0}'")]
+    InvalidTagPattern(String),
+
+    /// Invalid channel tag pattern (malformed regex).
+    #[error("Invalid channel pattern: '{0}'")]
+    InvalidChannelPattern(String),
+
+    /// Invalid update scope (unparseable version cap or semver requirement).
+    #[error("Invalid update scope: '{0}'")]
+    InvalidUpdateScope(String),
+
     /// IO error (cache file operations).
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -0,0 +1,25 @@
+//! Fixtures shared by the unit tests.
+
+use crate::store::RepoKey;
+use crate::types::Release;
+
+/// Builds a published, non-draft release with the given tag and notes.
+pub(crate) fn release(tag: &str, body: &str, prerelease: bool) -> Release {
+    Release {
+        tag_name: tag.to_string(),
+        name: None,
+        body: Some(body.to_string()),
+        prerelease,
+        draft: false,
+        html_url: format!("https://github.com/test/repo/releases/tag/{}", tag),
+        published_at: Some("2024-03-15T10:00:00Z".parse().unwrap()),
+    }
+}
+
+/// Builds the key for a repository on github.com.
+pub(crate) fn repo_key(repo: &str) -> RepoKey {
+    RepoKey {
+        base_url: "https://api.github.com".to_string(),
+        repo: repo.to_string(),
+    }
+}
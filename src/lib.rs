@@ -32,10 +32,36 @@
 //! }
 //! ```
 
+mod background;
+pub mod banner;
+mod cache;
+mod changelog;
+mod channel;
+mod clock;
 mod error;
+#[cfg(test)]
+mod fixtures;
 mod notifier;
+mod opt_out;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod store;
+pub mod testing;
 mod types;
+mod version;
 
+pub use background::BACKGROUND_REFRESH_ENV;
+pub use cache::{default_cache_dir, RepoCache};
+pub use changelog::{build_changelog, ChangelogOptions};
+pub use clock::{Clock, SystemClock};
 pub use error::{ReleaseNotifierError, Result};
 pub use notifier::ReleaseNotifier;
-pub use types::{Release, ReleaseNotifierConfig, VersionCheckResult};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::{CacheStore, FileStore, MemoryStore, RefreshGuard, RepoKey};
+pub use types::{
+    BackoffStatus, CachePolicy, CacheStatus, Channel, ChannelSource, CommitCheckResult, DataSource,
+    FailureKind, NormalizedVersion, Release, ReleaseNotifierConfig, TagPattern, UpdateKind,
+    UpdateScope, UpdateStatus, VersionCheckResult, VersionFallback,
+};
+pub use version::normalize_version;
@@ -1,33 +1,104 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use std::sync::Mutex;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::Utc;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use semver::Version;
+use tokio::sync::Mutex;
 use url::Url;
 
+use crate::background;
+use crate::cache::{
+    default_cache_dir, CacheEntry, CacheKey, CacheLock, CacheSource, CacheValue, FailureRecord,
+    NotificationState, RepoCache, DEFAULT_CACHE_FILE_NAME,
+};
+use crate::channel::ChannelMatcher;
 use crate::error::{ReleaseNotifierError, Result};
+use crate::opt_out;
+use crate::store::{CacheStore, FileStore, MemoryStore, RefreshGuard, RepoKey};
 use crate::types::{
-    CacheData, GitHubReleaseResponse, Release, ReleaseNotifierConfig, VersionCheckResult,
+    BackoffStatus, CachePolicy, CacheStatus, Channel, CommitCheckResult, DataSource, FailureKind,
+    GitHubCompareResponse, GitHubReleaseResponse, NormalizedVersion, Release,
+    ReleaseNotifierConfig, UpdateStatus, VersionCheckResult, VersionFallback,
+};
+use crate::version::{
+    classify_update, normalize_tag, normalize_version, parse_version, ScopeMatcher, TagMatcher,
 };
 
+/// How long to wait for a connection to GitHub.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a request to GitHub may take in all, so a stalled connection
+/// can't hold the refresh locks indefinitely.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// A notifier for checking GitHub release updates.
+///
+/// Cloning is cheap, and clones share the same cache.
+#[derive(Clone)]
 pub struct ReleaseNotifier {
-    config: ReleaseNotifierConfig,
+    config: Arc<ReleaseNotifierConfig>,
     client: Client,
-    cache: Mutex<Cache>,
-}
-
-struct Cache {
-    releases: Vec<Release>,
-    last_fetch_time: Option<i64>,
+    /// Where cached entries and failure records are kept.
+    store: Arc<dyn CacheStore>,
+    /// This repository's record in the store.
+    repo_key: RepoKey,
+    /// Per-key locks held while a key is refreshed, so that concurrent callers
+    /// share one request to GitHub.
+    refreshes: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    /// Where the data behind the latest result came from.
+    last_source: Arc<Mutex<Option<DataSource>>>,
+    /// Set when the latest fetched data couldn't be saved to the store.
+    unsaved: Arc<AtomicBool>,
+    /// Set when update checks are turned off by the user or the environment.
+    disabled: bool,
+    tag_matcher: Option<TagMatcher>,
+    scope_matcher: ScopeMatcher,
+    channel_matcher: ChannelMatcher,
 }
 
 impl ReleaseNotifier {
     /// Creates a new ReleaseNotifier with the given configuration.
     ///
-    /// If a cache file path is configured and the file exists, the cache will be loaded from disk.
-    pub fn new(config: ReleaseNotifierConfig) -> Result<Self> {
+    /// Cached data is kept in the configured cache store, else in the cache file
+    /// if a path is configured, else in memory. If the default cache location is
+    /// enabled, its directory is created as needed.
+    ///
+    /// This may create a directory with blocking I/O; use [`Self::new_async`] from async code.
+    pub fn new(mut config: ReleaseNotifierConfig) -> Result<Self> {
+        if let Some(dir) = Self::validate_config(&mut config, default_cache_dir)? {
+            fs::create_dir_all(&dir)?;
+        }
+        Self::validate_cache_file_path(&config)?;
+        Self::from_config(config, |name| std::env::var(name).ok())
+    }
+
+    /// Creates a new ReleaseNotifier without blocking the runtime.
+    ///
+    /// Behaves like [`Self::new`] otherwise.
+    pub async fn new_async(mut config: ReleaseNotifierConfig) -> Result<Self> {
+        if let Some(dir) = Self::validate_config(&mut config, default_cache_dir)? {
+            tokio::fs::create_dir_all(&dir).await?;
+        }
+        Self::validate_cache_file_path(&config)?;
+        Self::from_config(config, |name| std::env::var(name).ok())
+    }
+
+    /// Validates the repository and base URL, and resolves the default cache
+    /// location through `default_dir`.
+    ///
+    /// Returns the default cache directory if it was selected, so the caller can create it.
+    fn validate_config(
+        config: &mut ReleaseNotifierConfig,
+        default_dir: impl FnOnce() -> Option<PathBuf>,
+    ) -> Result<Option<PathBuf>> {
         // Validate repo format
         if !is_valid_repo_format(&config.repo) {
             return Err(ReleaseNotifierError::InvalidRepo(config.repo.clone()));
@@ -38,6 +109,19 @@ impl ReleaseNotifier {
             return Err(ReleaseNotifierError::InvalidBaseUrl(config.base_url.clone()));
         }
 
+        // Resolve the default cache location if opted in without an explicit path
+        if config.cache_file_path.is_none() && config.use_default_cache_location {
+            let dir = default_dir().ok_or(ReleaseNotifierError::NoDefaultCacheLocation)?;
+            let path = dir.join(DEFAULT_CACHE_FILE_NAME);
+            config.cache_file_path = Some(path.to_string_lossy().into_owned());
+            return Ok(Some(dir));
+        }
+
+        Ok(None)
+    }
+
+    /// Checks that the cache file's parent directory exists.
+    fn validate_cache_file_path(config: &ReleaseNotifierConfig) -> Result<()> {
         // Validate cache file path (parent directory must exist)
         if let Some(ref path) = config.cache_file_path {
             let path = Path::new(path);
@@ -50,36 +134,78 @@ impl ReleaseNotifier {
             }
         }
 
-        let client = Client::new();
+        Ok(())
+    }
 
-        let cache = if let Some(ref path) = config.cache_file_path {
-            Self::load_cache_from_disk(path).unwrap_or(Cache {
-                releases: Vec::new(),
-                last_fetch_time: None,
-            })
-        } else {
-            Cache {
-                releases: Vec::new(),
-                last_fetch_time: None,
-            }
+    /// Builds the notifier from a validated config, reading opt-out variables
+    /// through `env`.
+    fn from_config(
+        config: ReleaseNotifierConfig,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let tag_matcher = config
+            .tag_pattern
+            .as_ref()
+            .map(TagMatcher::new)
+            .transpose()?;
+        let scope_matcher = ScopeMatcher::new(&config.update_scope)?;
+        let channel_matcher = ChannelMatcher::new(&config.channel_source)?;
+
+        let store: Arc<dyn CacheStore> = match (&config.cache_store, &config.cache_file_path) {
+            (Some(store), _) => Arc::clone(store),
+            (None, Some(path)) => Arc::new(FileStore::new(path)),
+            (None, None) => Arc::new(MemoryStore::new()),
+        };
+        let repo_key = RepoKey {
+            base_url: config.base_url.clone(),
+            repo: config.repo.clone(),
         };
 
+        // A background refresh has no terminal, but the process that started it did
+        let without_terminal = config.disable_without_terminal
+            && !std::io::stdout().is_terminal()
+            && !background::is_background_refresh(&repo_key);
+        let disabled = without_terminal
+            || opt_out::is_opted_out(config.opt_out_env.as_deref(), config.auto_disable, env);
+
+        let client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+
         Ok(Self {
-            config,
+            config: Arc::new(config),
             client,
-            cache: Mutex::new(cache),
+            store,
+            repo_key,
+            refreshes: Arc::default(),
+            last_source: Arc::default(),
+            unsaved: Arc::default(),
+            disabled,
+            tag_matcher,
+            scope_matcher,
+            channel_matcher,
         })
     }
 
     /// Gets the latest stable release from the repository.
     ///
+    /// If `prefer_designated_latest` is enabled, the stable release GitHub marks
+    /// as "Latest" is returned, falling back to the most recently published one.
+    ///
     /// # Arguments
     /// * `include_prerelease` - If true, prereleases will be included in the search.
     ///
     /// # Returns
     /// The latest release, or None if no releases are found.
     pub async fn get_latest_release(&self, include_prerelease: bool) -> Result<Option<Release>> {
-        let releases = self.fetch_all_releases().await?;
+        if !include_prerelease {
+            if let (Some(designated), _) = self.designated_latest_release().await? {
+                return Ok(Some(designated));
+            }
+        }
+
+        let (releases, _) = self.fetch_all_releases().await?;
 
         let release = releases
             .into_iter()
@@ -95,7 +221,7 @@ impl ReleaseNotifier {
     /// # Returns
     /// The latest prerelease, or None if no prereleases are found.
     pub async fn get_latest_prerelease(&self) -> Result<Option<Release>> {
-        let releases = self.fetch_all_releases().await?;
+        let (releases, _) = self.fetch_all_releases().await?;
 
         let release = releases
             .into_iter()
@@ -109,99 +235,979 @@ impl ReleaseNotifier {
     ///
     /// # Arguments
     /// * `current_version` - The current version string (with or without 'v' prefix).
+    ///   Git-describe output and build metadata are mapped to the base release.
     /// * `is_prerelease` - If true, checks against prereleases; otherwise checks stable releases.
     ///
     /// # Returns
     /// A VersionCheckResult indicating if an update is available and the latest release.
+    /// If an update scope is configured, the latest release is the newest one inside
+    /// the scope, and a newer release outside it is reported as `out_of_scope_release`.
+    /// If update checks are turned off (see [`Self::is_disabled`]), GitHub isn't
+    /// contacted and the reason is `UpdateStatus::Disabled`.
     pub async fn check_version(
         &self,
         current_version: &str,
         is_prerelease: bool,
     ) -> Result<VersionCheckResult> {
-        let latest_release = if is_prerelease {
-            self.get_latest_prerelease().await?
+        self.check_version_matching(current_version, !is_prerelease, |r| {
+            r.prerelease == is_prerelease
+        })
+        .await
+    }
+
+    /// Gets the latest release offered to users on the given channel.
+    ///
+    /// Releases from the channel itself and from more stable channels are
+    /// considered, so `Channel::Beta` may return a beta, rc or stable release.
+    ///
+    /// # Returns
+    /// The latest release, or None if no releases are found.
+    pub async fn get_latest_on_channel(&self, channel: Channel) -> Result<Option<Release>> {
+        if channel == Channel::Stable {
+            if let (Some(designated), _) = self.designated_latest_release().await? {
+                return Ok(Some(designated));
+            }
+        }
+
+        let (releases, _) = self.fetch_all_releases().await?;
+
+        let release = releases
+            .into_iter()
+            .filter(|r| !r.draft && channel.accepts(self.release_channel(r)))
+            .max_by_key(|r| r.published_at);
+
+        Ok(release)
+    }
+
+    /// Checks if a newer version is available on the given channel.
+    ///
+    /// Like `check_version`, but offers releases from the channel and from more
+    /// stable channels instead of choosing between stable and prerelease.
+    ///
+    /// # Arguments
+    /// * `current_version` - The current version string (with or without 'v' prefix).
+    /// * `channel` - The channel the user is on.
+    pub async fn check_version_on_channel(
+        &self,
+        current_version: &str,
+        channel: Channel,
+    ) -> Result<VersionCheckResult> {
+        self.check_version_matching(current_version, channel == Channel::Stable, |r| {
+            channel.accepts(self.release_channel(r))
+        })
+        .await
+    }
+
+    /// Checks if a newer version is available using only cached data, without
+    /// contacting GitHub or needing an async runtime.
+    ///
+    /// This is meant for latency-sensitive callers such as shell prompts. The
+    /// cached data is used even if it has expired (the result is then marked
+    /// stale); keep it current by calling [`Self::refresh`] elsewhere. With the
+    /// `LookupTag` version fallback, a current version that isn't among the
+    /// cached releases is treated as unknown unless an earlier check looked up
+    /// its tag.
+    ///
+    /// # Returns
+    /// The same result as [`Self::check_version`], or None if the cache holds
+    /// no data for the check.
+    pub fn check_version_cached(
+        &self,
+        current_version: &str,
+        is_prerelease: bool,
+    ) -> Option<VersionCheckResult> {
+        if self.disabled {
+            return Some(disabled_result(current_version));
+        }
+
+        let record = self.store.get(&self.repo_key).ok().flatten()?;
+        let cached = |source| {
+            let (value, fetched_at) = cached_entry(&record, &self.cache_key(source))?;
+            let stale = !self.is_cache_fresh(Some(fetched_at));
+            Some((value, Freshness::cached(fetched_at, stale)))
+        };
+
+        let (CacheValue::Releases(releases), mut freshness) = cached(CacheSource::Releases)? else {
+            unreachable!("release keys hold release lists");
+        };
+        let releases = self.filter_by_tag_pattern(releases);
+
+        let stable_only = !is_prerelease;
+        let designated = if stable_only && self.config.prefer_designated_latest {
+            let (CacheValue::DesignatedLatest(designated), designated_freshness) =
+                cached(CacheSource::DesignatedLatest)?
+            else {
+                unreachable!("designated latest keys hold designated latest releases");
+            };
+            freshness = freshness.merge(designated_freshness);
+            self.filter_designated_latest(designated)
+        } else {
+            None
+        };
+
+        if let Ok(mut last_source) = self.last_source.try_lock() {
+            *last_source = Some(DataSource::Cache);
+        }
+
+        let selection = self.select_update(current_version, &releases, designated.as_ref(), |r| {
+            r.prerelease == is_prerelease
+        });
+        let current_release = self
+            .resolve_current_release_cached(&selection.normalized.base, &releases)
+            .or_else(|| {
+                let source = CacheSource::TagLookup {
+                    version: selection.normalized.base.clone(),
+                };
+                let (CacheValue::TagLookup(release), _) = cached(source)? else {
+                    unreachable!("tag lookup keys hold tag lookups");
+                };
+                release.map(CurrentRelease::Published)
+            })
+            .unwrap_or(CurrentRelease::Unknown);
+        Some(self.conclude_check(selection, &current_release, freshness))
+    }
+
+    /// Fetches the data [`Self::check_version`] uses from GitHub, if the cached
+    /// copy has expired.
+    ///
+    /// Call this from a background task or process to keep the cache current
+    /// for [`Self::check_version_cached`]. Unlike a check, it doesn't return
+    /// until the data is stored, whatever the cache policy. Fails with
+    /// `ReleaseNotifierError::Disabled` if update checks are turned off.
+    pub async fn refresh(&self) -> Result<()> {
+        if self.disabled {
+            return Err(ReleaseNotifierError::Disabled);
+        }
+
+        let mut sources = vec![CacheSource::Releases];
+        if self.config.prefer_designated_latest {
+            sources.push(CacheSource::DesignatedLatest);
+        }
+
+        for source in sources {
+            let key = self.cache_key(source);
+            let cached = self.cached_value(&key).await;
+            if !cached.is_some_and(|(_, fetched_at)| self.is_cache_fresh(Some(fetched_at))) {
+                self.refresh_key(&key).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refreshes the cached data in a detached child process, if it has expired.
+    ///
+    /// The current executable is started again without arguments and with
+    /// [`BACKGROUND_REFRESH_ENV`](crate::BACKGROUND_REFRESH_ENV) set, so the
+    /// user's command isn't repeated. It must check [`Self::is_background_refresh`]
+    /// before acting on its arguments and, if set, call
+    /// [`Self::run_background_refresh`] and exit. The child outlives the current
+    /// process, so short commands can read its result on their next run through
+    /// [`Self::check_version_cached`].
+    ///
+    /// # Returns
+    /// True if a child process was started. None is started if update checks
+    /// are turned off, the cached data is fresh, requests are backing off,
+    /// another background refresh is running, this process is itself a
+    /// background refresh, or the cache store can't be shared with the child
+    /// (see [`CacheStore::is_shared`]).
+    pub fn spawn_background_refresh(&self) -> Result<bool> {
+        let command = Command::new(std::env::current_exe()?);
+        self.spawn_background_refresh_with(command)
+    }
+
+    /// Like [`Self::spawn_background_refresh`], but runs the given command
+    /// instead of the current executable, e.g. a small helper binary.
+    pub fn spawn_background_refresh_with(&self, command: Command) -> Result<bool> {
+        // The child's result is only visible through a store both processes can read
+        let shared = self.store.is_shared();
+        if self.disabled || !shared || self.is_background_refresh() || !self.needs_refresh() {
+            return Ok(false);
+        }
+
+        // A running background refresh holds the lock
+        if CacheLock::try_acquire(&self.refresh_lock_path()).is_none() {
+            return Ok(false);
+        }
+
+        background::spawn_detached(command, &self.repo_key)?;
+        Ok(true)
+    }
+
+    /// Returns true if this process was started by [`Self::spawn_background_refresh`]
+    /// to refresh this notifier's repository.
+    pub fn is_background_refresh(&self) -> bool {
+        background::is_background_refresh(&self.repo_key)
+    }
+
+    /// Runs the refresh a background refresh process was started for.
+    ///
+    /// Does nothing if another background refresh of the repository is running.
+    pub async fn run_background_refresh(&self) -> Result<()> {
+        let Some(_lock) = CacheLock::try_acquire(&self.refresh_lock_path()) else {
+            return Ok(());
+        };
+        self.refresh().await
+    }
+
+    /// Returns true if update checks are turned off.
+    ///
+    /// Checks are turned off if the configured `opt_out_env` variable is set.
+    /// Unless `auto_disable` is off, they are also turned off if
+    /// `NO_UPDATE_NOTIFIER` or `DO_NOT_TRACK` is set, or in CI (`CI`,
+    /// `GITHUB_ACTIONS`, `GITLAB_CI`, `BUILDKITE`, ...). With
+    /// `disable_without_terminal`, they are turned off if stdout isn't a
+    /// terminal. The environment is read when the notifier is created.
+    ///
+    /// Checks then report `UpdateStatus::Disabled`, [`Self::refresh`] fails
+    /// with `ReleaseNotifierError::Disabled`, and no background refresh is
+    /// started. Explicit requests, such as [`Self::get_latest_release`], still
+    /// contact GitHub.
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Returns true if [`Self::refresh`] would fetch from GitHub.
+    fn needs_refresh(&self) -> bool {
+        if self.backoff_status().is_some() {
+            return false;
+        }
+
+        let record = self
+            .store
+            .get(&self.repo_key)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let fresh = |source| {
+            cached_entry(&record, &self.cache_key(source))
+                .is_some_and(|(_, fetched_at)| self.is_cache_fresh(Some(fetched_at)))
+        };
+        !fresh(CacheSource::Releases)
+            || (self.config.prefer_designated_latest && !fresh(CacheSource::DesignatedLatest))
+    }
+
+    /// Returns the path locked while a background refresh runs.
+    fn refresh_lock_path(&self) -> PathBuf {
+        background::refresh_lock_path(self.config.cache_file_path.as_deref(), &self.repo_key)
+    }
+
+    /// Checks for an update among the non-draft releases accepted by the filter.
+    ///
+    /// If `stable_only` is set and `prefer_designated_latest` is enabled, the
+    /// release GitHub marks as "Latest" takes precedence over publish dates.
+    async fn check_version_matching(
+        &self,
+        current_version: &str,
+        stable_only: bool,
+        filter: impl Fn(&Release) -> bool,
+    ) -> Result<VersionCheckResult> {
+        if self.disabled {
+            return Ok(disabled_result(current_version));
+        }
+
+        let (releases, mut freshness) = self.fetch_all_releases().await?;
+        let designated = if stable_only {
+            let (designated, designated_freshness) = self.designated_latest_release().await?;
+            freshness = freshness.merge(designated_freshness);
+            designated
         } else {
-            self.get_latest_release(false).await?
+            None
         };
 
-        let Some(latest) = latest_release else {
-            return Ok(VersionCheckResult {
+        let selection = self.select_update(current_version, &releases, designated.as_ref(), filter);
+        // The current release only matters if there is a release to compare it with
+        let current_release = match selection.latest {
+            Some(_) => {
+                self.resolve_current_release(&selection.normalized.base, &releases)
+                    .await
+            }
+            None => CurrentRelease::Unknown,
+        };
+
+        Ok(self.conclude_check(selection, &current_release, freshness))
+    }
+
+    /// Chooses the release to offer among the non-draft releases accepted by
+    /// the filter, preferring `designated` if it is among them.
+    fn select_update<'a>(
+        &self,
+        current_version: &str,
+        releases: &'a [Release],
+        designated: Option<&Release>,
+        filter: impl Fn(&Release) -> bool,
+    ) -> UpdateSelection<'a> {
+        // Builds past a release (git describe, build metadata) are compared as that release
+        let normalized = normalize_version(current_version);
+        let current = self.parse_release_version(&normalized.base);
+
+        let candidates: Vec<&Release> = releases.iter().filter(|r| !r.draft && filter(r)).collect();
+
+        let in_scope: Vec<&Release> = candidates
+            .iter()
+            .copied()
+            .filter(|r| self.is_in_scope(current.as_ref(), r))
+            .collect();
+
+        let designated_latest = find_by_tag(&in_scope, designated);
+        let latest =
+            designated_latest.or_else(|| in_scope.iter().copied().max_by_key(|r| r.published_at));
+
+        // Report the highest newer release the scope excluded. Backports can be
+        // published after the next major, so versions are compared, not dates
+        let out_of_scope_release = candidates
+            .iter()
+            .filter(|r| !in_scope.iter().any(|s| s.tag_name == r.tag_name))
+            .filter_map(|r| Some((self.parse_release_version(&r.tag_name)?, *r)))
+            .filter(|(version, _)| current.as_ref().is_none_or(|c| version > c))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, r)| r.clone());
+
+        UpdateSelection {
+            normalized,
+            current,
+            in_scope,
+            latest,
+            // Publish dates don't order backports, so compare the designated
+            // latest release by version number where possible
+            by_version: designated_latest.is_some(),
+            out_of_scope_release,
+        }
+    }
+
+    /// Compares the current release with the selected latest release.
+    fn conclude_check(
+        &self,
+        selection: UpdateSelection<'_>,
+        current_release: &CurrentRelease,
+        freshness: Freshness,
+    ) -> VersionCheckResult {
+        let UpdateSelection {
+            normalized,
+            current,
+            in_scope,
+            latest,
+            by_version,
+            out_of_scope_release,
+        } = selection;
+
+        let Some(latest) = latest else {
+            return VersionCheckResult {
                 update_available: false,
+                reason: UpdateStatus::NoReleases,
+                current_version: normalized,
                 latest_release: None,
-            });
+                out_of_scope_release,
+                update_kind: None,
+                versions_behind: 0,
+                stale: freshness.stale,
+                cache_age: freshness.cache_age(self.now_millis()),
+            };
         };
 
         // Check if current version is older than the latest
-        let releases = self.fetch_all_releases().await?;
-        let update_available = self.is_version_older(current_version, &latest, &releases);
+        let reason = match self.compare_to_latest(current_release, latest, by_version) {
+            // A build with commits past the latest release is ahead of it
+            UpdateStatus::UpToDate if normalized.commits_past > 0 => UpdateStatus::AheadOfLatest,
+            reason => reason,
+        };
+        let update_available = reason == UpdateStatus::UpdateAvailable;
 
-        Ok(VersionCheckResult {
+        let (update_kind, versions_behind) = if update_available {
+            let latest_version = self.parse_release_version(&latest.tag_name);
+            let update_kind = current
+                .as_ref()
+                .zip(latest_version.as_ref())
+                .and_then(|(c, l)| classify_update(c, l));
+            let versions_behind = in_scope
+                .iter()
+                .filter(|r| {
+                    self.compare_with_current(current_release, r, by_version)
+                        == Some(Ordering::Less)
+                        && self.compare_releases(r, latest, by_version) != Some(Ordering::Greater)
+                })
+                .count();
+            (update_kind, versions_behind)
+        } else {
+            (None, 0)
+        };
+
+        VersionCheckResult {
             update_available,
-            latest_release: Some(latest),
-        })
+            reason,
+            current_version: normalized,
+            latest_release: Some(latest.clone()),
+            out_of_scope_release,
+            update_kind,
+            versions_behind,
+            stale: freshness.stale,
+            cache_age: freshness.cache_age(self.now_millis()),
+        }
+    }
+
+    /// Gets the releases published after the current version.
+    ///
+    /// Draft releases are excluded; prereleases are included. Pass the result to
+    /// [`build_changelog`](crate::build_changelog) to merge their release notes.
+    ///
+    /// # Arguments
+    /// * `current_version` - The current version string (with or without 'v' prefix).
+    ///
+    /// # Returns
+    /// The newer releases ordered newest first, or an empty list if the current
+    /// version can't be found among the releases (and the version fallback,
+    /// if configured, can't place it either).
+    pub async fn releases_since(&self, current_version: &str) -> Result<Vec<Release>> {
+        let (releases, _) = self.fetch_all_releases().await?;
+        let normalized = normalize_version(current_version);
+        let current_release = self
+            .resolve_current_release(&normalized.base, &releases)
+            .await;
+
+        let mut newer: Vec<Release> = releases
+            .into_iter()
+            .filter(|r| !r.draft && self.is_newer_than_current(&current_release, r))
+            .collect();
+        newer.sort_by_key(|r| std::cmp::Reverse(r.published_at));
+
+        Ok(newer)
+    }
+
+    /// Checks if a build made from a commit is behind the tip of a branch.
+    ///
+    /// For tools installed from a branch rather than a release. Uses the compare
+    /// API, with the same caching, token and check interval as release checks.
+    ///
+    /// # Arguments
+    /// * `current_sha` - The commit SHA the current build was made from.
+    /// * `branch` - The branch to compare against (e.g. "main").
+    ///
+    /// # Returns
+    /// A CommitCheckResult with the number of commits behind and a compare URL.
+    pub async fn check_commit(&self, current_sha: &str, branch: &str) -> Result<CommitCheckResult> {
+        let key = self.cache_key(CacheSource::Comparison {
+            sha: current_sha.to_string(),
+            branch: branch.to_string(),
+        });
+
+        let (CacheValue::Comparison(result), _) = self.cached_or_fetch(&key).await? else {
+            unreachable!("comparison keys hold comparisons");
+        };
+        Ok(result)
     }
 
-    /// Clears both in-memory and disk cache.
+    /// Returns the pending backoff for this repository, if requests to GitHub
+    /// are paused after repeated failures.
+    ///
+    /// If several requests are paused, the one resuming last is returned. This
+    /// reads the cache store with blocking I/O.
+    pub fn backoff_status(&self) -> Option<BackoffStatus> {
+        if self.config.check_interval == 0 {
+            return None;
+        }
+
+        let now = self.now_millis();
+        let record = self.store.get(&self.repo_key).ok().flatten()?;
+        record
+            .failures
+            .keys()
+            .filter_map(|key| record.pending_failure(key, now))
+            .max_by_key(|failure| failure.retry_at())
+            .map(|failure| BackoffStatus {
+                kind: failure.kind,
+                consecutive_failures: failure.consecutive,
+                last_failure: timestamp(failure.failed_at),
+                retry_after: timestamp(failure.retry_at()),
+            })
+    }
+
+    /// Clears this repository's cached data, including any failure backoff.
+    ///
+    /// Only this repository's record is removed from a shared cache store; a
+    /// cache file is deleted once no entries remain. Which updates were shown,
+    /// snoozed or dismissed is kept.
     pub fn clear_cache(&self) {
-        let mut cache = self.cache.lock().unwrap();
-        cache.releases.clear();
-        cache.last_fetch_time = None;
+        let _guard = self.store.write_lock(&self.repo_key);
+        let notification = self
+            .store
+            .get(&self.repo_key)
+            .ok()
+            .flatten()
+            .map(|record| record.notification)
+            .unwrap_or_default();
+
+        let cleared = if notification.is_empty() {
+            self.store.invalidate(&self.repo_key)
+        } else {
+            let record = RepoCache {
+                notification,
+                ..RepoCache::default()
+            };
+            self.store.put(&self.repo_key, &record)
+        };
+        if cleared.is_ok() {
+            self.unsaved.store(false, AtomicOrdering::Relaxed);
+        }
+    }
+
+    /// Decides whether to show the user the update a check found, and if so
+    /// records that it was shown.
+    ///
+    /// Returns true the first time a new version is seen, and afterwards at
+    /// most once per `notify_interval`. Returns false if no update is
+    /// available, notifications are snoozed, or the user dismissed the version.
+    /// This reads and writes the cache store with blocking I/O, holding its
+    /// write lock so an update from another process isn't overwritten.
+    pub fn should_notify(&self, result: &VersionCheckResult) -> bool {
+        let Some(latest) = result
+            .latest_release
+            .as_ref()
+            .filter(|_| result.update_available)
+        else {
+            return false;
+        };
+
+        let now = self.now_millis();
+        let _guard = self.store.write_lock(&self.repo_key);
+        let mut record = self
+            .store
+            .get(&self.repo_key)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let state = &record.notification;
+
+        let dismissed = state
+            .dismissed_version
+            .as_deref()
+            .is_some_and(|version| self.is_same_version(version, &latest.tag_name));
+        let snoozed = state.snoozed_until.is_some_and(|until| now < until);
+        let reminded_recently = state.last_version.as_deref() == Some(latest.tag_name.as_str())
+            && state
+                .last_notified_at
+                .is_some_and(|at| now - at < self.config.notify_interval as i64);
+        if dismissed || snoozed || reminded_recently {
+            return false;
+        }
+
+        record.notification.last_version = Some(latest.tag_name.clone());
+        record.notification.last_notified_at = Some(now);
+        // If this can't be saved the user is reminded again, which beats never hearing of it
+        let _ = self.store.put(&self.repo_key, &record);
+        true
+    }
+
+    /// Suppresses update notifications for the given duration.
+    pub fn snooze(&self, duration: Duration) -> Result<()> {
+        let millis = i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+        let until = self.now_millis().saturating_add(millis);
+        self.update_notification(|state| state.snoozed_until = Some(until))
+    }
 
-        // Clear disk cache if configured
-        if let Some(ref path) = self.config.cache_file_path {
-            let _ = fs::remove_file(path);
+    /// Stops notifying about a version. Newer versions are still notified.
+    ///
+    /// The version may be given with or without a 'v' prefix.
+    pub fn dismiss(&self, version: &str) -> Result<()> {
+        self.update_notification(|state| state.dismissed_version = Some(version.to_string()))
+    }
+
+    /// Changes this repository's notification state in the cache store.
+    fn update_notification(&self, update: impl FnOnce(&mut NotificationState)) -> Result<()> {
+        let _guard = self.store.write_lock(&self.repo_key);
+        let mut record = self.store.get(&self.repo_key)?.unwrap_or_default();
+        update(&mut record.notification);
+        self.store.put(&self.repo_key, &record)
+    }
+
+    /// Returns true if two tags or version strings name the same version.
+    fn is_same_version(&self, a: &str, b: &str) -> bool {
+        match (self.parse_release_version(a), self.parse_release_version(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => a == b,
+        }
+    }
+
+    /// Returns a snapshot of this repository's cached data, for diagnostics.
+    pub async fn cache_status(&self) -> CacheStatus {
+        let record = self.load_record().await;
+        let now = self.now_millis();
+        let releases_key = self.cache_key(CacheSource::Releases);
+        let releases = record.entries.get(&releases_key.to_string());
+
+        // The release list is due once it expires, unless requests are paused
+        let expires_at = match releases {
+            Some(entry) if self.config.check_interval > 0 => {
+                entry.fetched_at + self.config.check_interval as i64
+            }
+            _ => now,
+        };
+        let next_fetch = match record.pending_failure(&releases_key.to_string(), now) {
+            Some(failure) if self.config.check_interval > 0 => expires_at.max(failure.retry_at()),
+            _ => expires_at,
+        };
+
+        CacheStatus {
+            last_fetch: record
+                .entries
+                .values()
+                .map(|entry| entry.fetched_at)
+                .max()
+                .map(timestamp),
+            next_fetch: timestamp(next_fetch),
+            entries: record.entries.len(),
+            has_etag: releases.is_some_and(|entry| entry.etag.is_some()),
+            last_source: *self.last_source.lock().await,
+            in_sync: !self.unsaved.load(AtomicOrdering::Relaxed),
         }
     }
 
     /// Fetches all releases, using cache if available and valid.
-    async fn fetch_all_releases(&self) -> Result<Vec<Release>> {
-        // Check if we have a valid cache
-        if self.config.check_interval > 0 {
-            let cache = self.cache.lock().unwrap();
-            if let Some(last_fetch) = cache.last_fetch_time {
-                let now = Utc::now().timestamp_millis();
-                if now - last_fetch < self.config.check_interval as i64
-                    && !cache.releases.is_empty()
-                {
-                    return Ok(cache.releases.clone());
+    ///
+    /// If a tag pattern is configured, only releases whose tags match it are returned.
+    async fn fetch_all_releases(&self) -> Result<(Vec<Release>, Freshness)> {
+        let key = self.cache_key(CacheSource::Releases);
+
+        let (CacheValue::Releases(releases), freshness) = self.cached_or_fetch(&key).await? else {
+            unreachable!("release keys hold release lists");
+        };
+
+        Ok((self.filter_by_tag_pattern(releases), freshness))
+    }
+
+    /// Gets the stable release GitHub marks as "Latest", if `prefer_designated_latest`
+    /// is enabled, using cache if available and valid.
+    ///
+    /// Returns None if the option is disabled, the repository has no designated
+    /// latest release, or it doesn't match the configured tag pattern.
+    async fn designated_latest_release(&self) -> Result<(Option<Release>, Freshness)> {
+        if !self.config.prefer_designated_latest {
+            return Ok((None, Freshness::default()));
+        }
+
+        let key = self.cache_key(CacheSource::DesignatedLatest);
+
+        let (CacheValue::DesignatedLatest(release), freshness) = self.cached_or_fetch(&key).await?
+        else {
+            unreachable!("designated latest keys hold designated latest releases");
+        };
+
+        Ok((self.filter_designated_latest(release), freshness))
+    }
+
+    /// Drops a designated latest release that can't be offered as a stable release.
+    fn filter_designated_latest(&self, release: Option<Release>) -> Option<Release> {
+        release.filter(|r| {
+            !r.draft
+                && !r.prerelease
+                && self
+                    .tag_matcher
+                    .as_ref()
+                    .is_none_or(|m| m.matches(&r.tag_name))
+        })
+    }
+
+    /// Builds the cache key for one of this repository's sources.
+    fn cache_key(&self, source: CacheSource) -> CacheKey {
+        self.repo_key.entry_key(source)
+    }
+
+    /// Reads this repository's record from the cache store.
+    ///
+    /// A store that can't be read is treated as empty.
+    async fn load_record(&self) -> RepoCache {
+        self.with_store(|store, repo_key| store.get(repo_key))
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Runs a cache store operation on the blocking thread pool.
+    async fn with_store<T, F>(&self, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn CacheStore, &RepoKey) -> Result<T> + Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        let repo_key = self.repo_key.clone();
+        match tokio::task::spawn_blocking(move || op(store.as_ref(), &repo_key)).await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(std::io::Error::other(e).into()),
+        }
+    }
+
+    /// Returns the cached value for a key and when it was fetched, whether or
+    /// not it is still within the check interval.
+    async fn cached_value(&self, key: &CacheKey) -> Option<(CacheValue, i64)> {
+        cached_entry(&self.load_record().await, key)
+    }
+
+    /// Returns the cached value for a key, fetching it if it has expired.
+    ///
+    /// Expired data may still be returned, marked stale, depending on the
+    /// configured cache policy.
+    async fn cached_or_fetch(&self, key: &CacheKey) -> Result<(CacheValue, Freshness)> {
+        let cached = self.cached_value(key).await;
+        let (value, freshness, source) = match cached {
+            Some((value, fetched_at)) if self.is_cache_fresh(Some(fetched_at)) => (
+                value,
+                Freshness::cached(fetched_at, false),
+                DataSource::Cache,
+            ),
+            cached => {
+                let stale = cached.map(|(value, fetched_at)| {
+                    (
+                        value,
+                        Freshness::cached(fetched_at, true),
+                        DataSource::Cache,
+                    )
+                });
+                match (self.config.cache_policy, stale) {
+                    (CachePolicy::StaleWhileRevalidate, Some(stale)) => {
+                        self.spawn_refresh(key);
+                        stale
+                    }
+                    (CachePolicy::StaleIfError, Some(stale)) => {
+                        self.refresh_key(key).await.unwrap_or(stale)
+                    }
+                    _ => self.refresh_key(key).await?,
                 }
             }
-        }
+        };
+
+        *self.last_source.lock().await = Some(source);
+        Ok((value, freshness))
+    }
 
-        let releases = self.fetch_from_github().await?;
+    /// Fetches the value for a key from GitHub and caches it.
+    ///
+    /// Only one refresh per key runs at a time, across all clones of this
+    /// notifier and, if the cache store has a lock, across other notifiers and
+    /// processes. The record is re-read once it is this caller's turn, so a
+    /// value fetched while it waited is reused instead of fetched again.
+    async fn refresh_key(&self, key: &CacheKey) -> Result<(CacheValue, Freshness, DataSource)> {
+        let requested_at = self.now_millis();
+        let id = key.to_string();
+        let turn = Arc::clone(self.refreshes.lock().await.entry(id.clone()).or_default());
+
+        let result = {
+            // Data fetched by a refresh this caller waited on can be reused
+            let (_turn, reuse_since) = match turn.try_lock() {
+                Ok(turn) => (turn, None),
+                Err(_) => (turn.lock().await, Some(requested_at)),
+            };
+            self.refresh_locked(key, reuse_since).await
+        };
 
+        // Forget the key's lock once no other caller is waiting on it
+        let mut refreshes = self.refreshes.lock().await;
+        drop(turn);
+        if refreshes
+            .get(&id)
+            .is_some_and(|turn| Arc::strong_count(turn) == 1)
         {
-            let mut cache = self.cache.lock().unwrap();
-            cache.releases = releases.clone();
-            cache.last_fetch_time = Some(Utc::now().timestamp_millis());
+            refreshes.remove(&id);
+        }
+
+        result
+    }
+
+    /// Refreshes a key once no other refresh of it is running in this process.
+    ///
+    /// Cached data fetched at or after `reuse_since` is returned as is. Otherwise
+    /// the fetch is conditional on the cached copy's ETag, if it has one.
+    async fn refresh_locked(
+        &self,
+        key: &CacheKey,
+        reuse_since: Option<i64>,
+    ) -> Result<(CacheValue, Freshness, DataSource)> {
+        let _guard: Option<RefreshGuard> = self
+            .with_store(|store, repo_key| Ok(store.lock(repo_key)))
+            .await?;
+
+        let record = self.load_record().await;
+        let cached = cached_entry(&record, key);
+        if let Some((value, fetched_at)) = &cached {
+            let refetched = reuse_since.is_some_and(|since| *fetched_at >= since);
+            if refetched || self.is_cache_fresh(Some(*fetched_at)) {
+                let freshness = Freshness::cached(*fetched_at, false);
+                return Ok((value.clone(), freshness, DataSource::Cache));
+            }
+        }
+
+        if let Some(error) = self.backoff_error(&record, key) {
+            return Err(error);
+        }
+
+        // Only revalidate a copy that can be served
+        let etag = cached
+            .is_some()
+            .then(|| record.entries.get(&key.to_string())?.etag.clone())
+            .flatten();
+
+        match self.fetch_source(&key.source, etag.as_deref()).await {
+            Ok(Fetched::Modified { value, etag }) => {
+                self.update_cache(key, value.clone(), etag).await;
+                Ok((value, Freshness::default(), DataSource::Network))
+            }
+            Ok(Fetched::NotModified) => {
+                let (value, _) = cached.expect("only cached copies are revalidated");
+                self.update_cache(key, value.clone(), etag).await;
+                Ok((value, Freshness::default(), DataSource::Revalidated))
+            }
+            Err(e) => {
+                self.record_failure(key, &e).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns a BackingOff error if requests for a key are paused after failures.
+    ///
+    /// Failures aren't remembered when caching is disabled.
+    fn backoff_error(&self, record: &RepoCache, key: &CacheKey) -> Option<ReleaseNotifierError> {
+        if self.config.check_interval == 0 {
+            return None;
         }
 
-        if let Some(ref path) = self.config.cache_file_path {
-            let _ = self.save_cache_to_disk(path);
+        let failure = record.pending_failure(&key.to_string(), self.now_millis())?;
+        Some(ReleaseNotifierError::BackingOff {
+            kind: failure.kind,
+            until: timestamp(failure.retry_at()),
+        })
+    }
+
+    /// Records a failed request for a key in the cache store.
+    async fn record_failure(&self, key: &CacheKey, error: &ReleaseNotifierError) {
+        let id = key.to_string();
+        let kind = FailureKind::of(error);
+        let failed_at = self.now_millis();
+        let _ = self
+            .with_store(move |store, repo_key| {
+                let _guard = store.write_lock(repo_key);
+                let mut record = store.get(repo_key)?.unwrap_or_default();
+                let consecutive = record
+                    .failures
+                    .get(&id)
+                    .map_or(0, |failure| failure.consecutive);
+                record.failures.insert(
+                    id,
+                    FailureRecord {
+                        kind,
+                        failed_at,
+                        consecutive: consecutive.saturating_add(1),
+                    },
+                );
+                store.put(repo_key, &record)
+            })
+            .await;
+    }
+
+    /// Refreshes a key in the background.
+    ///
+    /// If a refresh of the key is already running, this one waits for it and
+    /// reuses its result rather than fetching again.
+    fn spawn_refresh(&self, key: &CacheKey) {
+        let notifier = self.clone();
+        let key = key.clone();
+        tokio::spawn(async move {
+            // On failure the stale value stays cached and the next check retries
+            let _ = notifier.refresh_key(&key).await;
+        });
+    }
+
+    /// Fetches the data for a cache source from GitHub.
+    ///
+    /// The request is conditional on `etag`, if given.
+    async fn fetch_source(
+        &self,
+        source: &CacheSource,
+        etag: Option<&str>,
+    ) -> Result<Fetched<CacheValue>> {
+        let fetched = match source {
+            CacheSource::Releases => self
+                .fetch_from_github(etag)
+                .await?
+                .map(CacheValue::Releases),
+            CacheSource::DesignatedLatest => self
+                .fetch_designated_latest_from_github(etag)
+                .await?
+                .map(CacheValue::DesignatedLatest),
+            CacheSource::Comparison { sha, branch } => self
+                .fetch_comparison_from_github(sha, branch, etag)
+                .await?
+                .map(CacheValue::Comparison),
+            CacheSource::TagLookup { version } => Fetched::Modified {
+                value: CacheValue::TagLookup(self.fetch_release_by_version_tag(version).await?),
+                etag: None,
+            },
+        };
+        Ok(fetched)
+    }
+
+    /// Stores a freshly fetched or revalidated value in the cache store.
+    async fn update_cache(&self, key: &CacheKey, value: CacheValue, etag: Option<String>) {
+        let id = key.to_string();
+        let fetched_at = self.now_millis();
+        let saved = self
+            .with_store(move |store, repo_key| {
+                let _guard = store.write_lock(repo_key);
+                let mut record = store.get(repo_key)?.unwrap_or_default();
+                record.failures.remove(&id);
+                record.entries.insert(
+                    id,
+                    CacheEntry {
+                        value,
+                        fetched_at,
+                        etag,
+                    },
+                );
+                store.put(repo_key, &record)
+            })
+            .await;
+        self.unsaved.store(saved.is_err(), AtomicOrdering::Relaxed);
+    }
+
+    /// Returns the current time from the configured clock, in milliseconds
+    /// since the Unix epoch.
+    fn now_millis(&self) -> i64 {
+        self.config.clock.now().timestamp_millis()
+    }
+
+    /// Returns true if data fetched at `last_fetch` is still within the check interval.
+    fn is_cache_fresh(&self, last_fetch: Option<i64>) -> bool {
+        if self.config.check_interval == 0 {
+            return false;
         }
 
-        Ok(releases)
+        last_fetch.is_some_and(|last_fetch| {
+            let now = self.now_millis();
+            now - last_fetch < self.config.check_interval as i64
+        })
+    }
+
+    /// Drops releases whose tags don't match the configured tag pattern.
+    fn filter_by_tag_pattern(&self, releases: Vec<Release>) -> Vec<Release> {
+        match self.tag_matcher {
+            Some(ref matcher) => releases
+                .into_iter()
+                .filter(|r| matcher.matches(&r.tag_name))
+                .collect(),
+            None => releases,
+        }
     }
 
     /// Fetches releases directly from the GitHub API.
-    async fn fetch_from_github(&self) -> Result<Vec<Release>> {
+    async fn fetch_from_github(&self, etag: Option<&str>) -> Result<Fetched<Vec<Release>>> {
         let url = format!(
             "{}/repos/{}/releases",
             self.config.base_url, self.config.repo
         );
 
-        let mut request = self
-            .client
-            .get(&url)
-            .header("Accept", "application/vnd.github+json")
-            .header("X-GitHub-Api-Version", "2022-11-28")
-            .header("User-Agent", "gh-release-update-notifier-rs");
-
-        if let Some(ref token) = self.config.token {
-            request = request.header("Authorization", format!("Bearer {}", token));
-        }
-
-        let response = request.send().await?;
+        let Some((response, etag)) = self.send_conditional(&url, etag).await? else {
+            return Ok(Fetched::NotModified);
+        };
 
         if !response.status().is_success() {
             let status = response.status().as_u16();
@@ -213,69 +1219,454 @@ impl ReleaseNotifier {
 
         let releases: Vec<Release> = github_releases.into_iter().map(Release::from).collect();
 
-        Ok(releases)
+        Ok(Fetched::Modified {
+            value: releases,
+            etag,
+        })
+    }
+
+    /// Fetches the release GitHub marks as "Latest" directly from the GitHub API.
+    ///
+    /// Returns None if the repository has no designated latest release.
+    async fn fetch_designated_latest_from_github(
+        &self,
+        etag: Option<&str>,
+    ) -> Result<Fetched<Option<Release>>> {
+        let url = format!(
+            "{}/repos/{}/releases/latest",
+            self.config.base_url, self.config.repo
+        );
+
+        let Some((response, etag)) = self.send_conditional(&url, etag).await? else {
+            return Ok(Fetched::NotModified);
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Fetched::Modified { value: None, etag });
+        }
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(ReleaseNotifierError::ApiError { status, message });
+        }
+
+        let github_release: GitHubReleaseResponse = response.json().await?;
+
+        Ok(Fetched::Modified {
+            value: Some(Release::from(github_release)),
+            etag,
+        })
     }
 
-    /// Loads cache from disk.
-    fn load_cache_from_disk(path: &str) -> Option<Cache> {
-        let content = fs::read_to_string(path).ok()?;
-        let data: CacheData = serde_json::from_str(&content).ok()?;
-        Some(Cache {
-            releases: data.releases,
-            last_fetch_time: Some(data.last_fetch_time),
+    /// Compares a commit with a branch directly through the GitHub compare API.
+    async fn fetch_comparison_from_github(
+        &self,
+        current_sha: &str,
+        branch: &str,
+        etag: Option<&str>,
+    ) -> Result<Fetched<CommitCheckResult>> {
+        let url = format!(
+            "{}/repos/{}/compare/{}...{}",
+            self.config.base_url, self.config.repo, current_sha, branch
+        );
+
+        let Some((response, etag)) = self.send_conditional(&url, etag).await? else {
+            return Ok(Fetched::NotModified);
+        };
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(ReleaseNotifierError::ApiError { status, message });
+        }
+
+        let comparison: GitHubCompareResponse = response.json().await?;
+
+        Ok(Fetched::Modified {
+            value: CommitCheckResult::from(comparison),
+            etag,
         })
     }
 
-    /// Saves cache to disk.
-    fn save_cache_to_disk(&self, path: &str) -> Result<()> {
-        let cache = self.cache.lock().unwrap();
-        let data = CacheData {
-            releases: cache.releases.clone(),
-            last_fetch_time: cache.last_fetch_time.unwrap_or_else(|| Utc::now().timestamp_millis()),
+    /// Looks up the release for a version directly by its tag.
+    ///
+    /// Tries the tag with and without a 'v' prefix (and with the tag pattern's
+    /// prefix, if one is configured). Returns None if no such release exists,
+    /// which is cached like any other result.
+    async fn fetch_release_by_version_tag(&self, version: &str) -> Result<Option<Release>> {
+        for tag in self.tag_candidates(version) {
+            let url = format!(
+                "{}/repos/{}/releases/tags/{}",
+                self.config.base_url, self.config.repo, tag
+            );
+
+            let response = self.github_request(&url).send().await?;
+
+            if response.status() == StatusCode::NOT_FOUND {
+                continue;
+            }
+
+            if !response.status().is_success() {
+                let status = response.status().as_u16();
+                let message = response.text().await.unwrap_or_default();
+                return Err(ReleaseNotifierError::ApiError { status, message });
+            }
+
+            let github_release: GitHubReleaseResponse = response.json().await?;
+            let release = Release::from(github_release);
+
+            if self
+                .tag_matcher
+                .as_ref()
+                .is_none_or(|m| m.matches(&release.tag_name))
+            {
+                return Ok(Some(release));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the tag names a version might have been released under.
+    fn tag_candidates(&self, version: &str) -> Vec<String> {
+        if self
+            .tag_matcher
+            .as_ref()
+            .is_some_and(|m| m.matches(version))
+        {
+            return vec![version.to_string()];
+        }
+
+        let prefix = match self.tag_matcher {
+            Some(TagMatcher::Prefix(ref prefix)) => prefix.as_str(),
+            _ => "",
         };
-        let content = serde_json::to_string(&data)?;
-        fs::write(path, content)?;
-        Ok(())
+        let bare = version.strip_prefix('v').unwrap_or(version);
+
+        vec![
+            format!("{}v{}", prefix, bare),
+            format!("{}{}", prefix, bare),
+        ]
+    }
+
+    /// Builds a GET request to the GitHub API with the standard headers and token.
+    fn github_request(&self, url: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .get(url)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header("User-Agent", "gh-release-update-notifier-rs");
+
+        if let Some(ref token) = self.config.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        request
+    }
+
+    /// Sends a GET request to the GitHub API, conditional on `etag` if given.
+    ///
+    /// Returns None if GitHub reports that the copy with that ETag is current,
+    /// and otherwise the response along with its ETag, if it has one.
+    async fn send_conditional(
+        &self,
+        url: &str,
+        etag: Option<&str>,
+    ) -> Result<Option<(Response, Option<String>)>> {
+        let mut request = self.github_request(url);
+        if let Some(etag) = etag {
+            request = request.header("If-None-Match", etag);
+        }
+
+        let response = request.send().await?;
+        if etag.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let etag = response
+            .headers()
+            .get("ETag")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok(Some((response, etag)))
     }
 
     /// Finds a release by its version tag.
     ///
-    /// Handles version strings with or without 'v' prefix.
+    /// Handles version strings with or without 'v' prefix. If a tag pattern is
+    /// configured, its prefix is stripped from tags (and from the version, if present).
     fn find_release_by_version<'a>(
         &self,
         version: &str,
-        releases: &'a [Release],
+        releases: impl IntoIterator<Item = &'a Release>,
     ) -> Option<&'a Release> {
-        let normalized_version = version.strip_prefix('v').unwrap_or(version);
+        let matcher = self.tag_matcher.as_ref();
+        let normalized_version = normalize_tag(version, matcher);
 
-        releases.iter().find(|r| {
-            let normalized_tag = r.tag_name.strip_prefix('v').unwrap_or(&r.tag_name);
-            normalized_tag == normalized_version
-        })
+        releases
+            .into_iter()
+            .find(|r| normalize_tag(&r.tag_name, matcher) == normalized_version)
     }
 
-    /// Determines if the current version is older than the latest release.
-    /// Uses publish date for comparison, not semantic versioning to handle
-    /// varying versioning schemes.
-    fn is_version_older(
+    /// Returns true if the release falls inside the configured update scope.
+    fn is_in_scope(&self, current: Option<&Version>, release: &Release) -> bool {
+        let version = self.parse_release_version(&release.tag_name);
+        self.scope_matcher.allows(current, version.as_ref())
+    }
+
+    /// Works out what is known about the current version.
+    ///
+    /// Looks for the current version among the fetched releases first, then
+    /// applies the configured version fallback.
+    async fn resolve_current_release(
         &self,
         current_version: &str,
-        latest: &Release,
         releases: &[Release],
-    ) -> bool {
-        // Find the current version's release to get its publish date
-        let Some(current) = self.find_release_by_version(current_version, releases) else {
-            // If we can't find the current version, assume it's not older
-            // (user might be on an unreleased/dev version)
-            return false;
+    ) -> CurrentRelease {
+        if let Some(current) = self.resolve_current_release_cached(current_version, releases) {
+            return current;
+        }
+
+        let key = self.cache_key(CacheSource::TagLookup {
+            version: current_version.to_string(),
+        });
+        // The releases are known, so a failed lookup only leaves the current version unplaced
+        let current = match self.cached_or_fetch(&key).await {
+            Ok((CacheValue::TagLookup(release), _)) => release.map(CurrentRelease::Published),
+            Ok(_) => unreachable!("tag lookup keys hold tag lookups"),
+            Err(_) => None,
+        };
+        current.unwrap_or(CurrentRelease::Unknown)
+    }
+
+    /// Places the current version without contacting GitHub.
+    ///
+    /// Returns None if that takes a tag lookup (the `LookupTag` fallback).
+    fn resolve_current_release_cached(
+        &self,
+        current_version: &str,
+        releases: &[Release],
+    ) -> Option<CurrentRelease> {
+        if let Some(current) = self.find_release_by_version(current_version, releases) {
+            return Some(CurrentRelease::Published(current.clone()));
+        }
+
+        // The user might be on an unreleased/dev version, or on a release that
+        // was deleted, re-tagged or isn't on the first page of results
+        let current = match self.config.version_fallback {
+            VersionFallback::Disabled => None,
+            VersionFallback::CompareVersions => self
+                .parse_release_version(current_version)
+                .map(CurrentRelease::Parsed),
+            VersionFallback::LookupTag => return None,
         };
 
+        Some(current.unwrap_or(CurrentRelease::Unknown))
+    }
+
+    /// Determines how the current version relates to the latest release.
+    /// Uses publish date for comparison, not semantic versioning to handle
+    /// varying versioning schemes. Version numbers are only compared when the
+    /// current version isn't a known release and the fallback allows it, or
+    /// when `by_version` is set.
+    fn compare_to_latest(
+        &self,
+        current: &CurrentRelease,
+        latest: &Release,
+        by_version: bool,
+    ) -> UpdateStatus {
+        // If we can't find the current version, assume it's not older
+        if let CurrentRelease::Unknown = current {
+            return UpdateStatus::UnknownCurrentVersion;
+        }
+
+        match self.compare_with_current(current, latest, by_version) {
+            Some(Ordering::Less) => UpdateStatus::UpdateAvailable,
+            Some(Ordering::Equal) => UpdateStatus::UpToDate,
+            Some(Ordering::Greater) => UpdateStatus::AheadOfLatest,
+            None => UpdateStatus::Indeterminate,
+        }
+    }
+
+    /// Returns true if the release is newer than the current version.
+    fn is_newer_than_current(&self, current: &CurrentRelease, release: &Release) -> bool {
+        self.compare_with_current(current, release, false) == Some(Ordering::Less)
+    }
+
+    /// Orders the current version relative to a release, or None if they can't be compared.
+    fn compare_with_current(
+        &self,
+        current: &CurrentRelease,
+        release: &Release,
+        by_version: bool,
+    ) -> Option<Ordering> {
+        match current {
+            CurrentRelease::Published(current) => {
+                self.compare_releases(current, release, by_version)
+            }
+            CurrentRelease::Parsed(current_version) => self
+                .parse_release_version(&release.tag_name)
+                .map(|release_version| current_version.cmp(&release_version)),
+            CurrentRelease::Unknown => None,
+        }
+    }
+
+    /// Orders two releases by publish date, or by version number if `by_version`
+    /// is set and both tags can be parsed.
+    fn compare_releases(&self, a: &Release, b: &Release, by_version: bool) -> Option<Ordering> {
+        if by_version {
+            let versions = self
+                .parse_release_version(&a.tag_name)
+                .zip(self.parse_release_version(&b.tag_name));
+            if let Some((a_version, b_version)) = versions {
+                return Some(a_version.cmp(&b_version));
+            }
+        }
+
         // Compare by publish date to get a true representation of "newer"
-        match (current.published_at, latest.published_at) {
-            (Some(current_date), Some(latest_date)) => current_date < latest_date,
-            _ => false,
+        a.published_at
+            .zip(b.published_at)
+            .map(|(a_date, b_date)| a_date.cmp(&b_date))
+    }
+
+    /// Works out the channel a release was published on.
+    fn release_channel(&self, release: &Release) -> Channel {
+        self.channel_matcher
+            .channel_of(release, self.tag_matcher.as_ref())
+    }
+
+    /// Parses a tag or version string, stripping the tag pattern prefix if present.
+    fn parse_release_version(&self, tag: &str) -> Option<Version> {
+        parse_version(normalize_tag(tag, self.tag_matcher.as_ref()))
+    }
+}
+
+/// Returns the result of a check made while update checks are turned off.
+fn disabled_result(current_version: &str) -> VersionCheckResult {
+    VersionCheckResult {
+        update_available: false,
+        reason: UpdateStatus::Disabled,
+        current_version: normalize_version(current_version),
+        latest_release: None,
+        out_of_scope_release: None,
+        update_kind: None,
+        versions_behind: 0,
+        stale: false,
+        cache_age: None,
+    }
+}
+
+/// Returns a record's value for a key and when it was fetched, unless it
+/// can't be served from the cache.
+fn cached_entry(record: &RepoCache, key: &CacheKey) -> Option<(CacheValue, i64)> {
+    let entry = record.entries.get(&key.to_string())?;
+    // An empty release list is refetched rather than served from cache
+    let usable = entry.value.is_for(&key.source)
+        && !matches!(&entry.value, CacheValue::Releases(releases) if releases.is_empty());
+    usable.then(|| (entry.value.clone(), entry.fetched_at))
+}
+
+/// Converts milliseconds since the Unix epoch to a timestamp.
+fn timestamp(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap_or_default()
+}
+
+/// The result of a request that may be conditional on a cached copy's ETag.
+enum Fetched<T> {
+    /// New data, with the ETag GitHub returned for it, if any.
+    Modified { value: T, etag: Option<String> },
+    /// The cached copy is still current.
+    NotModified,
+}
+
+impl<T> Fetched<T> {
+    /// Converts the data, if any was fetched.
+    fn map<U>(self, f: impl FnOnce(T) -> U) -> Fetched<U> {
+        match self {
+            Self::Modified { value, etag } => Fetched::Modified {
+                value: f(value),
+                etag,
+            },
+            Self::NotModified => Fetched::NotModified,
+        }
+    }
+}
+
+/// How fresh the cached data behind a result is.
+#[derive(Debug, Clone, Copy, Default)]
+struct Freshness {
+    /// Whether data past the check interval was served.
+    stale: bool,
+    /// When the oldest data used was fetched, if any of it came from the cache.
+    cached_at: Option<i64>,
+}
+
+impl Freshness {
+    /// Describes data served from the cache.
+    fn cached(fetched_at: i64, stale: bool) -> Self {
+        Self {
+            stale,
+            cached_at: Some(fetched_at),
+        }
+    }
+
+    /// Combines the freshness of two pieces of data used for one result.
+    fn merge(self, other: Self) -> Self {
+        let cached_at = match (self.cached_at, other.cached_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            stale: self.stale || other.stale,
+            cached_at,
         }
     }
+
+    /// Returns the age at `now` of the oldest cached data used.
+    fn cache_age(&self, now: i64) -> Option<Duration> {
+        self.cached_at.map(|fetched_at| {
+            let age = now - fetched_at;
+            Duration::from_millis(age.max(0) as u64)
+        })
+    }
+}
+
+/// Finds the release with the same tag as `target` among the releases.
+fn find_by_tag<'a>(releases: &[&'a Release], target: Option<&Release>) -> Option<&'a Release> {
+    let target = target?;
+    releases
+        .iter()
+        .copied()
+        .find(|r| r.tag_name == target.tag_name)
+}
+
+/// What is known about the current version when comparing it with releases.
+enum CurrentRelease {
+    /// The current version is a published release.
+    Published(Release),
+    /// The current version isn't a known release, but its version number can be compared.
+    Parsed(Version),
+    /// Nothing is known about the current version.
+    Unknown,
+}
+
+/// The releases an update check chooses between, before the current release
+/// is placed among them.
+struct UpdateSelection<'a> {
+    /// The current version, normalized to the release it was built from.
+    normalized: NormalizedVersion,
+    /// The parsed current version, if it is a valid version.
+    current: Option<Version>,
+    /// The candidate releases inside the update scope.
+    in_scope: Vec<&'a Release>,
+    /// The release to offer, if any.
+    latest: Option<&'a Release>,
+    /// Whether `latest` is the designated latest release, compared by version.
+    by_version: bool,
+    /// The newest candidate release, if the update scope excluded it.
+    out_of_scope_release: Option<Release>,
 }
 
 /// Maximum length for a GitHub username/organization name.
@@ -321,6 +1712,7 @@ fn is_valid_repo_name(name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::cache_dir_from;
 
     #[test]
     fn test_invalid_repo_format() {
@@ -408,6 +1800,60 @@ mod tests {
         assert_eq!(path, "/nonexistent/directory/cache.json");
     }
 
+    #[test]
+    fn test_invalid_tag_pattern() {
+        let config = ReleaseNotifierConfig::new("owner/repo")
+            .tag_pattern(crate::types::TagPattern::Regex("cli-(".to_string()));
+        let result = ReleaseNotifier::new(config);
+
+        let Err(ReleaseNotifierError::InvalidTagPattern(pattern)) = result else {
+            panic!("Expected InvalidTagPattern error");
+        };
+        assert_eq!(pattern, "cli-(");
+    }
+
+    #[test]
+    fn test_invalid_update_scope() {
+        let config = ReleaseNotifierConfig::new("owner/repo").update_scope(
+            crate::types::UpdateScope::Requirement("not a range".to_string()),
+        );
+        let result = ReleaseNotifier::new(config);
+
+        let Err(ReleaseNotifierError::InvalidUpdateScope(scope)) = result else {
+            panic!("Expected InvalidUpdateScope error");
+        };
+        assert_eq!(scope, "not a range");
+    }
+
+    #[test]
+    fn test_default_cache_location() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let env = |name: &str| (name == "XDG_CACHE_HOME").then(|| cache_home.path().into());
+
+        let mut config = ReleaseNotifierConfig::new("owner/repo").use_default_cache_location(true);
+        let dir = ReleaseNotifier::validate_config(&mut config, || cache_dir_from(env)).unwrap();
+
+        let expected = cache_home.path().join("relnotify");
+        assert_eq!(dir.as_deref(), Some(expected.as_path()));
+        assert_eq!(
+            config.cache_file_path.as_deref(),
+            expected.join("cache.json").to_str()
+        );
+    }
+
+    #[test]
+    fn test_explicit_cache_path_overrides_default_location() {
+        let cache_file = tempfile::NamedTempFile::new().unwrap();
+        let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+        let config = ReleaseNotifierConfig::new("owner/repo")
+            .cache_file_path(&cache_path)
+            .use_default_cache_location(true);
+        let notifier = ReleaseNotifier::new(config).unwrap();
+
+        assert_eq!(notifier.config.cache_file_path, Some(cache_path));
+    }
+
     #[test]
     fn test_valid_base_url() {
         let config = ReleaseNotifierConfig::new("owner/repo")
@@ -415,4 +1861,53 @@ mod tests {
         let result = ReleaseNotifier::new(config);
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_opt_out_env_disables_checks() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let releases = serde_json::json!([{
+            "tag_name": "v2.0.0",
+            "name": null,
+            "body": null,
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.0.0",
+            "published_at": "2024-03-15T10:00:00Z"
+        }]);
+        Mock::given(method("GET"))
+            .and(path("/repos/test/repo/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(releases))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // The tool's own variable is honored even with the generic opt-outs off
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .opt_out_env("MYTOOL_NO_UPDATE_CHECK")
+            .auto_disable(false)
+            .base_url(mock_server.uri());
+        let env = |name: &str| (name == "MYTOOL_NO_UPDATE_CHECK").then(|| "1".to_string());
+        let notifier = ReleaseNotifier::from_config(config, env).unwrap();
+        assert!(notifier.is_disabled());
+
+        let result = notifier.check_version("1.0.0", false).await.unwrap();
+        assert_eq!(result.reason, UpdateStatus::Disabled);
+        assert!(!result.update_available);
+        assert!(result.latest_release.is_none());
+
+        let cached = notifier.check_version_cached("1.0.0", false).unwrap();
+        assert_eq!(cached.reason, UpdateStatus::Disabled);
+        assert!(matches!(
+            notifier.refresh().await,
+            Err(ReleaseNotifierError::Disabled)
+        ));
+        assert!(!notifier.should_notify(&result));
+
+        // Explicit requests still contact GitHub
+        let latest = notifier.get_latest_release(false).await.unwrap().unwrap();
+        assert_eq!(latest.tag_name, "v2.0.0");
+    }
 }
@@ -0,0 +1,90 @@
+/// Generic environment variables users set to turn off update checks.
+const OPT_OUT_ENVS: &[&str] = &["NO_UPDATE_NOTIFIER", "DO_NOT_TRACK"];
+
+/// Environment variables set by CI services.
+const CI_ENVS: &[&str] = &[
+    "CI",
+    "CONTINUOUS_INTEGRATION",
+    "BUILD_ID",
+    "RUN_ID",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TRAVIS",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "TF_BUILD",
+    "APPVEYOR",
+    "BITBUCKET_BUILD_NUMBER",
+    "CODEBUILD_BUILD_ID",
+    "DRONE",
+    "SEMAPHORE",
+];
+
+/// Decides whether the environment turns update checks off.
+///
+/// The tool's own variable (`tool_env`) is always honored. With `auto_disable`,
+/// checks are also turned off by the generic opt-out variables and in CI.
+pub(crate) fn is_opted_out(
+    tool_env: Option<&str>,
+    auto_disable: bool,
+    env: impl Fn(&str) -> Option<String>,
+) -> bool {
+    // "0" and "false" are treated as unset, so CI=false doesn't count as CI
+    let is_set = |name: &str| {
+        env(name).is_some_and(|value| {
+            let value = value.trim();
+            !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+        })
+    };
+
+    if tool_env.is_some_and(is_set) {
+        return true;
+    }
+
+    auto_disable
+        && (OPT_OUT_ENVS.iter().any(|name| is_set(name)) || CI_ENVS.iter().any(|name| is_set(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn test_opt_out_signals() {
+        let opted_out = |vars| is_opted_out(Some("MYTOOL_NO_UPDATE_CHECK"), true, env(vars));
+
+        assert!(!opted_out(&[]));
+        assert!(opted_out(&[("MYTOOL_NO_UPDATE_CHECK", "1")]));
+        assert!(opted_out(&[("NO_UPDATE_NOTIFIER", "1")]));
+        assert!(opted_out(&[("DO_NOT_TRACK", "1")]));
+        assert!(opted_out(&[("CI", "true")]));
+        assert!(opted_out(&[("GITHUB_ACTIONS", "true")]));
+        assert!(!opted_out(&[("CI", "false")]));
+        assert!(!opted_out(&[("DO_NOT_TRACK", "0")]));
+    }
+
+    #[test]
+    fn test_tool_env_is_honored_without_auto_disable() {
+        let vars = [("MYTOOL_NO_UPDATE_CHECK", "1"), ("CI", "true")];
+        assert!(is_opted_out(
+            Some("MYTOOL_NO_UPDATE_CHECK"),
+            false,
+            env(&vars)
+        ));
+        assert!(!is_opted_out(
+            Some("OTHER_NO_UPDATE_CHECK"),
+            false,
+            env(&vars)
+        ));
+    }
+}
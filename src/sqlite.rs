@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::cache::{RepoCache, CACHE_SCHEMA_VERSION};
+use crate::error::{ReleaseNotifierError, Result};
+use crate::store::{CacheStore, RepoKey};
+
+/// How long a write waits for another connection to release the database.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A [`CacheStore`] backed by a SQLite database, available with the `sqlite` feature.
+///
+/// Each repository is one row in the `relnotify_cache` table, so a single
+/// database can hold records for many repositories and be shared between
+/// processes. Rows written by a newer version of this library are read but
+/// never modified.
+#[derive(Debug)]
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Opens the database at `path`, creating it and the cache table as needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path).map_err(store_error)?)
+    }
+
+    /// Opens a private in-memory database.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    /// Uses an existing connection, creating the cache table if it is missing.
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(store_error)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS relnotify_cache (
+                repo_key TEXT PRIMARY KEY,
+                schema_version INTEGER NOT NULL,
+                data TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(store_error)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl CacheStore for SqliteStore {
+    fn get(&self, key: &RepoKey) -> Result<Option<RepoCache>> {
+        let conn = self.conn.lock().unwrap();
+        let data: Option<String> = conn
+            .query_row(
+                "SELECT data FROM relnotify_cache WHERE repo_key = ?1",
+                params![key.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(store_error)?;
+
+        // Unreadable rows are treated as missing
+        Ok(data.and_then(|data| serde_json::from_str(&data).ok()))
+    }
+
+    fn put(&self, key: &RepoKey, record: &RepoCache) -> Result<()> {
+        let data = serde_json::to_string(record)?;
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(store_error)?;
+
+        let schema_version: Option<u32> = tx
+            .query_row(
+                "SELECT schema_version FROM relnotify_cache WHERE repo_key = ?1",
+                params![key.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(store_error)?;
+        if let Some(version) = schema_version.filter(|&v| v > CACHE_SCHEMA_VERSION) {
+            return Err(ReleaseNotifierError::UnsupportedCacheSchema(version));
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO relnotify_cache (repo_key, schema_version, data)
+             VALUES (?1, ?2, ?3)",
+            params![key.to_string(), CACHE_SCHEMA_VERSION, data],
+        )
+        .map_err(store_error)?;
+        tx.commit().map_err(store_error)
+    }
+
+    fn invalidate(&self, key: &RepoKey) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM relnotify_cache WHERE repo_key = ?1 AND schema_version <= ?2",
+            params![key.to_string(), CACHE_SCHEMA_VERSION],
+        )
+        .map_err(store_error)?;
+        Ok(())
+    }
+
+    fn is_shared(&self) -> bool {
+        true
+    }
+}
+
+/// Wraps a SQLite error for [`ReleaseNotifierError::CacheStore`].
+fn store_error(e: rusqlite::Error) -> ReleaseNotifierError {
+    ReleaseNotifierError::CacheStore(Box::new(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::repo_key;
+
+    #[test]
+    fn test_sqlite_store_round_trip() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let (first, second) = (repo_key("owner/first"), repo_key("owner/second"));
+        assert!(store.get(&first).unwrap().is_none());
+
+        store.put(&first, &RepoCache::default()).unwrap();
+        store.put(&second, &RepoCache::default()).unwrap();
+        assert!(store.get(&first).unwrap().is_some());
+
+        store.invalidate(&first).unwrap();
+        assert!(store.get(&first).unwrap().is_none());
+        assert!(store.get(&second).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_sqlite_store_leaves_newer_schema_rows_untouched() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let key = repo_key("owner/repo");
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO relnotify_cache VALUES (?1, ?2, '{}')",
+                params![key.to_string(), CACHE_SCHEMA_VERSION + 1],
+            )
+            .unwrap();
+
+        assert!(store.get(&key).unwrap().is_some());
+        assert!(matches!(
+            store.put(&key, &RepoCache::default()),
+            Err(ReleaseNotifierError::UnsupportedCacheSchema(_))
+        ));
+        store.invalidate(&key).unwrap();
+        assert!(store.get(&key).unwrap().is_some());
+    }
+}
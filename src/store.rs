@@ -0,0 +1,326 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::cache::{CacheFile, CacheKey, CacheLock, CacheSource, RepoCache};
+use crate::error::Result;
+
+/// A lock returned by [`CacheStore::lock`], released when dropped.
+pub type RefreshGuard = Box<dyn Any + Send>;
+
+/// Identifies a repository's record in a [`CacheStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RepoKey {
+    /// The GitHub API base URL, e.g. "https://api.github.com".
+    pub base_url: String,
+    /// The repository in "owner/repo" format.
+    pub repo: String,
+}
+
+impl RepoKey {
+    /// Returns the key of one of this repository's cache entries.
+    pub(crate) fn entry_key(&self, source: CacheSource) -> CacheKey {
+        CacheKey {
+            base_url: self.base_url.clone(),
+            repo: self.repo.clone(),
+            source,
+        }
+    }
+
+    /// Returns a form of the key that can be used in a file name.
+    pub(crate) fn file_name(&self) -> String {
+        self.to_string()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect()
+    }
+}
+
+impl fmt::Display for RepoKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.base_url, self.repo)
+    }
+}
+
+/// Storage for cached release data, keyed by repository.
+///
+/// A store can be shared by any number of notifiers through
+/// [`ReleaseNotifierConfig::cache_store`](crate::ReleaseNotifierConfig::cache_store).
+/// Methods are called from blocking tasks, so implementations may do blocking I/O.
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// Returns the record for a repository, or None if there is none.
+    fn get(&self, key: &RepoKey) -> Result<Option<RepoCache>>;
+
+    /// Stores the record for a repository, replacing any previous one.
+    fn put(&self, key: &RepoKey, record: &RepoCache) -> Result<()>;
+
+    /// Removes the record for a repository.
+    fn invalidate(&self, key: &RepoKey) -> Result<()>;
+
+    /// Takes a lock held while a repository's data is refreshed from GitHub.
+    ///
+    /// Stores shared between processes can use this so that only one of them
+    /// fetches, while the others wait and then reuse the result. The default
+    /// implementation doesn't lock.
+    fn lock(&self, key: &RepoKey) -> Option<RefreshGuard> {
+        let _ = key;
+        None
+    }
+
+    /// Takes a lock held while a repository's record is read, changed and written back.
+    ///
+    /// Unlike [`Self::lock`], it is only held around one update and never while
+    /// waiting on GitHub. Stores shared between processes can use this so that
+    /// concurrent updates don't overwrite each other. The default
+    /// implementation doesn't lock.
+    fn write_lock(&self, key: &RepoKey) -> Option<RefreshGuard> {
+        let _ = key;
+        None
+    }
+
+    /// Returns true if other processes can read what is stored here.
+    ///
+    /// Background refreshes are only started for such stores, since the child
+    /// process leaves its result in the store. The default is false.
+    fn is_shared(&self) -> bool {
+        false
+    }
+}
+
+/// A [`CacheStore`] that keeps records in memory for the life of the process.
+///
+/// This is the default when no cache file is configured.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<RepoKey, RepoCache>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &RepoKey) -> Result<Option<RepoCache>> {
+        Ok(self.records.lock().unwrap().get(key).cloned())
+    }
+
+    fn put(&self, key: &RepoKey, record: &RepoCache) -> Result<()> {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(key.clone(), record.clone());
+        Ok(())
+    }
+
+    fn invalidate(&self, key: &RepoKey) -> Result<()> {
+        self.records.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// A [`CacheStore`] backed by a JSON file that several repositories and
+/// processes can share.
+///
+/// Writes replace the file atomically. Refreshes of a repository hold an
+/// advisory lock on a sibling `<path>.<repository>.lock` file, and updates one
+/// on `<path>.write.lock`. Files written by a newer version of this
+/// library are read but never modified.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store for the cache file at `path`. The file is created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&self, key: &RepoKey) -> Option<CacheFile> {
+        CacheFile::load(&self.path, &key.base_url, &key.repo)
+    }
+
+    /// Takes the lock on the sibling file `<path>.<name>.lock`.
+    fn acquire(&self, name: &str) -> Option<RefreshGuard> {
+        let mut path = self.path.as_os_str().to_owned();
+        path.push(".");
+        path.push(name);
+        CacheLock::acquire(Path::new(&path)).map(|lock| Box::new(lock) as RefreshGuard)
+    }
+}
+
+impl CacheStore for FileStore {
+    fn get(&self, key: &RepoKey) -> Result<Option<RepoCache>> {
+        let record = self
+            .load(key)
+            .map(|file| file.repo_cache(&key.base_url, &key.repo))
+            .filter(|record| !record.is_empty());
+        Ok(record)
+    }
+
+    fn put(&self, key: &RepoKey, record: &RepoCache) -> Result<()> {
+        // Missing and unreadable files are replaced; newer ones load read-only and aren't saved
+        let mut file = self.load(key).unwrap_or_default();
+        file.replace_repo(&key.base_url, &key.repo, record.clone());
+        file.save(&self.path)
+    }
+
+    /// Removes the repository's entries, deleting the file once no entries remain.
+    fn invalidate(&self, key: &RepoKey) -> Result<()> {
+        let Some(mut file) = self.load(key) else {
+            // Unreadable files are removed rather than left in place
+            return remove_if_exists(&self.path);
+        };
+
+        // Leave caches written by a newer version of this library untouched,
+        // even when their layout can't be read
+        if file.is_read_only() {
+            return Ok(());
+        }
+
+        file.remove_repo(&key.base_url, &key.repo);
+        if file.is_empty() {
+            remove_if_exists(&self.path)
+        } else {
+            file.save(&self.path)
+        }
+    }
+
+    /// Locks the repository only, so refreshes of other repositories sharing
+    /// the file run at the same time.
+    fn lock(&self, key: &RepoKey) -> Option<RefreshGuard> {
+        self.acquire(&key.file_name())
+    }
+
+    /// Locks the whole file, since every write rewrites it.
+    fn write_lock(&self, _key: &RepoKey) -> Option<RefreshGuard> {
+        self.acquire("write")
+    }
+
+    fn is_shared(&self) -> bool {
+        true
+    }
+}
+
+/// Removes a file, treating a missing file as success.
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{CacheEntry, CacheValue, CACHE_SCHEMA_VERSION};
+    use crate::error::ReleaseNotifierError;
+    use crate::fixtures::repo_key;
+
+    fn record(key: &RepoKey) -> RepoCache {
+        let mut record = RepoCache::default();
+        record.entries.insert(
+            key.entry_key(CacheSource::Releases).to_string(),
+            CacheEntry {
+                value: CacheValue::Releases(Vec::new()),
+                fetched_at: 0,
+                etag: None,
+            },
+        );
+        record
+    }
+
+    fn assert_round_trip(store: &dyn CacheStore) {
+        let (first, second) = (repo_key("owner/first"), repo_key("owner/second"));
+        assert!(store.get(&first).unwrap().is_none());
+
+        store.put(&first, &record(&first)).unwrap();
+        store.put(&second, &record(&second)).unwrap();
+        assert_eq!(store.get(&first).unwrap().unwrap().entries.len(), 1);
+
+        store.invalidate(&first).unwrap();
+        assert!(store.get(&first).unwrap().is_none());
+        assert!(store.get(&second).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_memory_store() {
+        assert_round_trip(&MemoryStore::new());
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("cache.json"));
+        assert_round_trip(&store);
+
+        // The file is removed once the last repository is invalidated
+        store.invalidate(&repo_key("owner/second")).unwrap();
+        assert!(!store.path().exists());
+    }
+
+    #[test]
+    fn test_file_store_write_lock_does_not_wait_on_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("cache.json"));
+        let key = repo_key("owner/repo");
+
+        let _refresh = store.lock(&key).unwrap();
+        let started = std::time::Instant::now();
+        assert!(store.write_lock(&key).is_some());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_file_store_locks_each_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("cache.json"));
+
+        let _first = store.lock(&repo_key("owner/first")).unwrap();
+        let started = std::time::Instant::now();
+        assert!(store.lock(&repo_key("owner/second")).is_some());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_file_store_leaves_newer_schema_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("cache.json"));
+        let key = repo_key("owner/repo");
+
+        // A layout this version can't parse
+        let content = serde_json::json!({
+            "schema_version": CACHE_SCHEMA_VERSION + 1,
+            "records": { "owner/repo": { "releases": [] } }
+        })
+        .to_string();
+        fs::write(store.path(), &content).unwrap();
+
+        assert!(store.get(&key).unwrap().is_none());
+        assert!(matches!(
+            store.put(&key, &record(&key)),
+            Err(ReleaseNotifierError::UnsupportedCacheSchema(_))
+        ));
+        store.invalidate(&key).unwrap();
+        assert_eq!(fs::read_to_string(store.path()).unwrap(), content);
+    }
+
+    #[test]
+    fn test_repo_cache_round_trips_through_serde() {
+        let key = repo_key("owner/repo");
+        let json = serde_json::to_string(&record(&key)).unwrap();
+        let parsed: RepoCache = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+    }
+}
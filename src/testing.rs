@@ -0,0 +1,72 @@
+//! Helpers for testing code that uses a [`ReleaseNotifier`](crate::ReleaseNotifier).
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::clock::Clock;
+
+/// A [`Clock`] that only moves when told to.
+///
+/// Share it with a notifier through
+/// [`ReleaseNotifierConfig::clock`](crate::ReleaseNotifierConfig::clock), then
+/// advance it to expire cached data or end a backoff without sleeping.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at `start`.
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    /// Creates a clock stopped at the current system time, truncated to the
+    /// millisecond precision cached timestamps are stored with.
+    pub fn starting_now() -> Self {
+        let now = Utc::now().timestamp_millis();
+        Self::new(DateTime::from_timestamp_millis(now).unwrap_or_default())
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, by: Duration) {
+        let by = chrono::Duration::from_std(by).unwrap_or(chrono::Duration::MAX);
+        let mut now = self.now.lock().unwrap();
+        *now = now
+            .checked_add_signed(by)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    }
+
+    /// Sets the clock to a specific time.
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.now.lock().unwrap() = time;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_only_moves_when_advanced() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = ManualClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now(), start + chrono::Duration::seconds(90));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -1,6 +1,14 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::clock::{Clock, SystemClock};
+use crate::error::ReleaseNotifierError;
+use crate::store::CacheStore;
+
 /// Configuration for the ReleaseNotifier.
 #[derive(Debug, Clone)]
 pub struct ReleaseNotifierConfig {
@@ -9,10 +17,45 @@ pub struct ReleaseNotifierConfig {
     /// The interval in milliseconds between checks. Default is 3600000 (1 hour).
     /// Set to 0 to disable caching.
     pub check_interval: u64,
-    /// Optional path to a file for persisting cache to disk.
+    /// The minimum interval in milliseconds between reminders about the same
+    /// update. Default is 86400000 (1 day).
+    pub notify_interval: u64,
+    /// Optional path to a file for persisting cache to disk. The file may be
+    /// shared by notifiers for different repositories.
     pub cache_file_path: Option<String>,
+    /// If true and no cache file path is set, the cache is persisted under the
+    /// default location (`$XDG_CACHE_HOME/relnotify/`). Default is false.
+    pub use_default_cache_location: bool,
+    /// Optional store for cached data, taking precedence over the cache file.
+    /// Without either, data is cached in memory for the notifier's lifetime.
+    pub cache_store: Option<Arc<dyn CacheStore>>,
+    /// How expired cache data is used once the check interval has passed.
+    pub cache_policy: CachePolicy,
     /// Optional GitHub API token for authentication.
     pub token: Option<String>,
+    /// Optional pattern restricting which release tags belong to this product.
+    pub tag_pattern: Option<TagPattern>,
+    /// Which releases `check_version` may offer, relative to the current version.
+    pub update_scope: UpdateScope,
+    /// How to place the current version when it isn't among the fetched releases.
+    pub version_fallback: VersionFallback,
+    /// How to work out which channel a release was published on.
+    pub channel_source: ChannelSource,
+    /// If true, the stable release GitHub marks as "Latest" is preferred over
+    /// the most recently published one. Default is false.
+    pub prefer_designated_latest: bool,
+    /// The source of the current time for cache expiry and backoff. Defaults
+    /// to the system clock.
+    pub clock: Arc<dyn Clock>,
+    /// An environment variable that turns off update checks when set (e.g.
+    /// "MYTOOL_NO_UPDATE_CHECK").
+    pub opt_out_env: Option<String>,
+    /// If true, update checks are also turned off by `NO_UPDATE_NOTIFIER` or
+    /// `DO_NOT_TRACK`, and in CI. Default is true.
+    pub auto_disable: bool,
+    /// If true, update checks are turned off when stdout isn't a terminal.
+    /// Default is false.
+    pub disable_without_terminal: bool,
     /// Base URL for GitHub API (for testing). Defaults to "https://api.github.com".
     pub(crate) base_url: String,
 }
@@ -22,9 +65,22 @@ impl ReleaseNotifierConfig {
     pub fn new(repo: impl Into<String>) -> Self {
         Self {
             repo: repo.into(),
-            check_interval: 3600000, // 1 hour default
+            check_interval: 3600000,   // 1 hour default
+            notify_interval: 86400000, // 1 day default
             cache_file_path: None,
+            use_default_cache_location: false,
+            cache_store: None,
+            cache_policy: CachePolicy::Strict,
             token: None,
+            tag_pattern: None,
+            update_scope: UpdateScope::Any,
+            version_fallback: VersionFallback::Disabled,
+            channel_source: ChannelSource::Prerelease,
+            prefer_designated_latest: false,
+            clock: Arc::new(SystemClock),
+            opt_out_env: None,
+            auto_disable: true,
+            disable_without_terminal: false,
             base_url: "https://api.github.com".to_string(),
         }
     }
@@ -42,17 +98,222 @@ impl ReleaseNotifierConfig {
         self
     }
 
+    /// Sets the minimum interval in milliseconds between reminders about the same update.
+    pub fn notify_interval(mut self, interval: u64) -> Self {
+        self.notify_interval = interval;
+        self
+    }
+
     /// Sets the cache file path.
     pub fn cache_file_path(mut self, path: impl Into<String>) -> Self {
         self.cache_file_path = Some(path.into());
         self
     }
 
+    /// Persists the cache under the default location (`$XDG_CACHE_HOME/relnotify/`)
+    /// unless an explicit cache file path is set.
+    pub fn use_default_cache_location(mut self, enabled: bool) -> Self {
+        self.use_default_cache_location = enabled;
+        self
+    }
+
+    /// Sets the store for cached data, which may be shared with other notifiers.
+    ///
+    /// Takes precedence over the cache file path and default cache location.
+    pub fn cache_store(mut self, store: Arc<dyn CacheStore>) -> Self {
+        self.cache_store = Some(store);
+        self
+    }
+
+    /// Sets how expired cache data is used once the check interval has passed.
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = policy;
+        self
+    }
+
     /// Sets the GitHub API token.
     pub fn token(mut self, token: impl Into<String>) -> Self {
         self.token = Some(token.into());
         self
     }
+
+    /// Sets the tag pattern used to select releases in a multi-product repository.
+    pub fn tag_pattern(mut self, pattern: TagPattern) -> Self {
+        self.tag_pattern = Some(pattern);
+        self
+    }
+
+    /// Sets the update scope used by `check_version`.
+    pub fn update_scope(mut self, scope: UpdateScope) -> Self {
+        self.update_scope = scope;
+        self
+    }
+
+    /// Sets the fallback used when the current version isn't among the fetched releases.
+    pub fn version_fallback(mut self, fallback: VersionFallback) -> Self {
+        self.version_fallback = fallback;
+        self
+    }
+
+    /// Sets how release channels are worked out.
+    pub fn channel_source(mut self, source: ChannelSource) -> Self {
+        self.channel_source = source;
+        self
+    }
+
+    /// Sets whether the release GitHub marks as "Latest" is preferred when
+    /// resolving the latest stable release.
+    pub fn prefer_designated_latest(mut self, prefer: bool) -> Self {
+        self.prefer_designated_latest = prefer;
+        self
+    }
+
+    /// Sets the source of the current time, e.g. a
+    /// [`ManualClock`](crate::testing::ManualClock) in tests.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the environment variable users set to turn off update checks.
+    pub fn opt_out_env(mut self, name: impl Into<String>) -> Self {
+        self.opt_out_env = Some(name.into());
+        self
+    }
+
+    /// Sets whether update checks are turned off by the generic opt-out
+    /// variables and in CI.
+    pub fn auto_disable(mut self, enabled: bool) -> Self {
+        self.auto_disable = enabled;
+        self
+    }
+
+    /// Sets whether update checks are turned off when stdout isn't a terminal,
+    /// e.g. when output is piped or the tool runs from cron.
+    pub fn disable_without_terminal(mut self, enabled: bool) -> Self {
+        self.disable_without_terminal = enabled;
+        self
+    }
+}
+
+/// How expired cache data is used once the check interval has passed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CachePolicy {
+    /// Fetch from GitHub, failing if it can't be reached.
+    #[default]
+    Strict,
+    /// Return the expired data immediately and refresh it in the background.
+    StaleWhileRevalidate,
+    /// Fetch from GitHub, falling back to the expired data if the fetch fails.
+    StaleIfError,
+}
+
+/// Selects release tags belonging to one product and extracts their version.
+///
+/// Useful for monorepos that publish tags such as `cli-v1.4.0` and
+/// `server-v2.1.0` from the same repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagPattern {
+    /// Tags must start with this prefix (e.g. "cli-"); the remainder is the version.
+    Prefix(String),
+    /// Tags must match this regular expression. The version is taken from the
+    /// capture group named `version`, or the first capture group otherwise.
+    Regex(String),
+}
+
+/// Limits which releases are offered as updates, relative to the current version.
+///
+/// Useful for long-term support lines, where users on 3.x shouldn't be told about 4.0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UpdateScope {
+    /// Any newer release may be offered.
+    #[default]
+    Any,
+    /// Only releases with the same major version as the current version.
+    SameMajor,
+    /// Only releases with the same major and minor version as the current version.
+    SameMinor,
+    /// Only releases up to and including this version (e.g. "3.9.9").
+    MaxVersion(String),
+    /// Only releases matching this semver requirement (e.g. ">=3.0, <4.0").
+    Requirement(String),
+}
+
+/// How to compare the current version when it isn't among the fetched releases.
+///
+/// This happens for dev builds, for releases that were deleted or re-tagged, and
+/// for versions old enough to fall off the first page of results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VersionFallback {
+    /// Report `UnknownCurrentVersion` and no update.
+    #[default]
+    Disabled,
+    /// Compare parsed version numbers instead of publish dates.
+    CompareVersions,
+    /// Look up the release by tag through `/releases/tags/{tag}` and compare
+    /// publish dates. The result, including not finding the tag, is cached
+    /// like the releases. With a regex tag pattern, the current version must
+    /// be passed as the full tag.
+    LookupTag,
+}
+
+/// A named release stream, ordered from least to most stable.
+///
+/// A user on a channel is offered releases from that channel and from any more
+/// stable one: beta users get beta, rc and stable releases, but never nightlies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Channel {
+    /// Nightly or other automated builds.
+    Nightly,
+    /// Alpha releases.
+    Alpha,
+    /// Beta releases.
+    Beta,
+    /// Release candidates.
+    Rc,
+    /// Stable releases.
+    Stable,
+}
+
+impl Channel {
+    /// Maps a prerelease identifier or channel name (e.g. "beta", "rc") to a channel.
+    ///
+    /// Matching is case-insensitive. Returns None for unrecognized identifiers.
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier.to_ascii_lowercase().as_str() {
+            "nightly" | "dev" | "canary" | "snapshot" => Some(Self::Nightly),
+            "alpha" | "a" => Some(Self::Alpha),
+            "beta" | "b" | "preview" => Some(Self::Beta),
+            "rc" | "pre" | "cr" => Some(Self::Rc),
+            "stable" | "release" => Some(Self::Stable),
+            _ => None,
+        }
+    }
+
+    /// Returns true if a user on this channel should be offered releases from `other`.
+    pub fn accepts(self, other: Channel) -> bool {
+        other >= self
+    }
+}
+
+/// How to work out which channel a release was published on.
+///
+/// Whatever the source, releases it can't classify fall back to the semver
+/// prerelease identifiers of their tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChannelSource {
+    /// Use the semver prerelease identifiers of the tag (e.g. `v2.0.0-beta.1`).
+    #[default]
+    Prerelease,
+    /// Use the first regular expression that matches the tag.
+    TagPatterns(Vec<(Channel, String)>),
+    /// Look for a marker in the release body, followed by the channel name
+    /// (e.g. "channel:" to match a `<!-- channel: beta -->` line).
+    BodyMarker(String),
 }
 
 /// Represents a GitHub release.
@@ -79,8 +340,192 @@ pub struct Release {
 pub struct VersionCheckResult {
     /// Whether an update is available.
     pub update_available: bool,
+    /// Why an update is or isn't available.
+    pub reason: UpdateStatus,
+    /// The current version, mapped to the release it is based on.
+    pub current_version: NormalizedVersion,
     /// The latest release, if any.
     pub latest_release: Option<Release>,
+    /// A newer release that exists but falls outside the configured update scope.
+    pub out_of_scope_release: Option<Release>,
+    /// How big the jump to the latest release is, if an update is available and
+    /// both versions can be parsed.
+    pub update_kind: Option<UpdateKind>,
+    /// The number of releases published after the current version, up to and
+    /// including the latest release.
+    pub versions_behind: usize,
+    /// Whether the result is based on cached data past the check interval,
+    /// served because of the configured [`CachePolicy`].
+    pub stale: bool,
+    /// How old the cached data behind the result is, or None if it was fetched
+    /// from GitHub during this check.
+    pub cache_age: Option<Duration>,
+}
+
+/// A build's version string mapped to the release it is based on.
+///
+/// Produced by [`normalize_version`](crate::normalize_version) for
+/// git-describe output and versions with semver build metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedVersion {
+    /// The release version the build is based on (e.g. "v1.4.0").
+    pub base: String,
+    /// The number of commits the build is past the base release.
+    pub commits_past: u32,
+    /// The abbreviated commit SHA the build was made from, if known.
+    pub commit: Option<String>,
+    /// The semver build metadata (after '+'), if any.
+    pub build_metadata: Option<String>,
+    /// Whether the build was made from a modified working tree.
+    pub dirty: bool,
+}
+
+impl fmt::Display for NormalizedVersion {
+    /// Formats as the base version, or "N commits past <base>" for builds past a release.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.commits_past {
+            0 => write!(f, "{}", self.base),
+            1 => write!(f, "1 commit past {}", self.base),
+            n => write!(f, "{} commits past {}", n, self.base),
+        }
+    }
+}
+
+/// The outcome of a version check, explaining `update_available`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UpdateStatus {
+    /// The current version is the latest release.
+    UpToDate,
+    /// A newer release is available.
+    UpdateAvailable,
+    /// The current version wasn't found among the releases (e.g. a dev build).
+    UnknownCurrentVersion,
+    /// The current version was published after the latest release
+    /// (e.g. running a prerelease while checking stable releases), or is a
+    /// build past the latest release.
+    AheadOfLatest,
+    /// No releases matched the check (after filtering drafts, prereleases,
+    /// tag pattern and update scope).
+    NoReleases,
+    /// The releases couldn't be compared because a publish date is missing.
+    Indeterminate,
+    /// Update checks are turned off by the user or the environment (e.g. in
+    /// CI), so GitHub wasn't contacted.
+    Disabled,
+}
+
+/// The size of an available update, based on semantic versioning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum UpdateKind {
+    /// Only the prerelease identifiers changed (e.g. 2.0.0-beta.1 to 2.0.0).
+    Prerelease,
+    /// The patch version changed.
+    Patch,
+    /// The minor version changed.
+    Minor,
+    /// The major version changed.
+    Major,
+}
+
+/// What kind of failure paused requests to GitHub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// GitHub couldn't be reached (DNS, connection or timeout errors).
+    Network,
+    /// The repository or resource wasn't found (or is private and no token was sent).
+    NotFound,
+    /// The token was missing or rejected.
+    Unauthorized,
+    /// The API rate limit was exceeded.
+    RateLimited,
+    /// GitHub returned a server error.
+    ServerError,
+    /// Any other failure, such as an unparseable response.
+    Other,
+}
+
+impl FailureKind {
+    /// Classifies an error returned while fetching from GitHub.
+    pub(crate) fn of(error: &ReleaseNotifierError) -> Self {
+        match error {
+            ReleaseNotifierError::HttpError(_) => Self::Network,
+            ReleaseNotifierError::ApiError { status, .. } => match status {
+                401 => Self::Unauthorized,
+                403 | 429 => Self::RateLimited,
+                404 => Self::NotFound,
+                500..=599 => Self::ServerError,
+                _ => Self::Other,
+            },
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A pause in requests to GitHub after repeated failures.
+///
+/// Each consecutive failure doubles the pause, starting at one minute and
+/// capped at one day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackoffStatus {
+    /// The kind of the latest failure.
+    pub kind: FailureKind,
+    /// The number of failures in a row.
+    pub consecutive_failures: u32,
+    /// When the latest failure happened.
+    pub last_failure: DateTime<Utc>,
+    /// When requests resume.
+    pub retry_after: DateTime<Utc>,
+}
+
+/// Where the data behind the latest result came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DataSource {
+    /// Served from the cache without contacting GitHub, whether fresh or stale.
+    Cache,
+    /// Fetched from GitHub.
+    Network,
+    /// GitHub confirmed the cached copy is current (HTTP 304 for its ETag).
+    Revalidated,
+}
+
+/// A snapshot of a notifier's cache, as returned by
+/// [`ReleaseNotifier::cache_status`](crate::ReleaseNotifier::cache_status).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStatus {
+    /// When data for this repository was last fetched from GitHub, if any is cached.
+    pub last_fetch: Option<DateTime<Utc>>,
+    /// When the release list will next be fetched from GitHub, accounting for
+    /// any failure backoff. This is now or in the past if the next check fetches.
+    pub next_fetch: DateTime<Utc>,
+    /// The number of cached entries for this repository.
+    pub entries: usize,
+    /// Whether the cached release list has an ETag, so its next fetch is a
+    /// conditional request.
+    pub has_etag: bool,
+    /// Where the data behind this notifier's latest result came from, if it
+    /// has returned any.
+    pub last_source: Option<DataSource>,
+    /// False if the latest data fetched by this notifier couldn't be saved, so
+    /// the cache store holds older data than was last returned.
+    pub in_sync: bool,
+}
+
+/// The result of a commit check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitCheckResult {
+    /// Whether the branch has commits the current build doesn't.
+    pub update_available: bool,
+    /// The number of commits on the branch that the current build doesn't have.
+    pub commits_behind: u64,
+    /// When the newest commit on the branch was committed, if any are newer.
+    pub latest_commit_date: Option<DateTime<Utc>>,
+    /// The URL of the comparison between the current commit and the branch.
+    pub compare_url: String,
 }
 
 /// Internal structure for GitHub API response.
@@ -109,9 +554,45 @@ impl From<GitHubReleaseResponse> for Release {
     }
 }
 
-/// Internal cache data structure for disk persistence.
-#[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct CacheData {
-    pub releases: Vec<Release>,
-    pub last_fetch_time: i64,
+/// Internal structure for GitHub compare API response.
+#[derive(Debug, Deserialize)]
+pub(crate) struct GitHubCompareResponse {
+    pub html_url: String,
+    pub ahead_by: u64,
+    #[serde(default)]
+    pub commits: Vec<GitHubCompareCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GitHubCompareCommit {
+    pub commit: GitHubCommitDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GitHubCommitDetails {
+    pub committer: Option<GitHubCommitSignature>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct GitHubCommitSignature {
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl From<GitHubCompareResponse> for CommitCheckResult {
+    fn from(response: GitHubCompareResponse) -> Self {
+        // The branch is the head of the comparison, so commits it is "ahead by"
+        // are the ones the current build is missing
+        let latest_commit_date = response
+            .commits
+            .iter()
+            .filter_map(|c| c.commit.committer.as_ref()?.date)
+            .max();
+
+        Self {
+            update_available: response.ahead_by > 0,
+            commits_behind: response.ahead_by,
+            latest_commit_date,
+            compare_url: response.html_url,
+        }
+    }
 }
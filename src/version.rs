@@ -0,0 +1,377 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use semver::{Version, VersionReq};
+
+use crate::error::{ReleaseNotifierError, Result};
+use crate::types::{NormalizedVersion, TagPattern, UpdateKind, UpdateScope};
+
+/// Matches a git-describe suffix such as "-12-gabc1234" or "-12-gabc1234-dirty".
+static GIT_DESCRIBE_SUFFIX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<base>.+?)-(?P<commits>\d+)-g(?P<sha>[0-9a-fA-F]{4,40})(?P<dirty>-dirty)?$")
+        .unwrap()
+});
+
+/// Compiled form of a [`TagPattern`], used to select tags and extract versions from them.
+#[derive(Debug, Clone)]
+pub(crate) enum TagMatcher {
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl TagMatcher {
+    /// Compiles a tag pattern, validating regular expressions up front.
+    pub(crate) fn new(pattern: &TagPattern) -> Result<Self> {
+        match pattern {
+            TagPattern::Prefix(prefix) => Ok(Self::Prefix(prefix.clone())),
+            TagPattern::Regex(source) => {
+                let regex = Regex::new(source)
+                    .map_err(|_| ReleaseNotifierError::InvalidTagPattern(source.clone()))?;
+                // The version must come from a capture group
+                if regex.captures_len() < 2 {
+                    return Err(ReleaseNotifierError::InvalidTagPattern(source.clone()));
+                }
+                Ok(Self::Regex(regex))
+            }
+        }
+    }
+
+    /// Extracts the version portion of a tag, or None if the tag doesn't match.
+    ///
+    /// For regular expressions, a group named `version` takes precedence over
+    /// the first capture group.
+    pub(crate) fn extract<'a>(&self, tag: &'a str) -> Option<&'a str> {
+        match self {
+            Self::Prefix(prefix) => tag.strip_prefix(prefix.as_str()),
+            Self::Regex(regex) => {
+                let captures = regex.captures(tag)?;
+                captures
+                    .name("version")
+                    .or_else(|| captures.get(1))
+                    .map(|m| m.as_str())
+            }
+        }
+    }
+
+    /// Returns true if the tag belongs to this pattern.
+    pub(crate) fn matches(&self, tag: &str) -> bool {
+        self.extract(tag).is_some()
+    }
+}
+
+/// Reduces a tag or version string to the bare version used for comparison.
+///
+/// When a tag matcher is configured and the string matches it, the product
+/// prefix is removed first. A leading 'v' is always stripped.
+pub(crate) fn normalize_tag<'a>(tag: &'a str, matcher: Option<&TagMatcher>) -> &'a str {
+    let version = matcher.and_then(|m| m.extract(tag)).unwrap_or(tag);
+    version.strip_prefix('v').unwrap_or(version)
+}
+
+/// Maps a build's version string to the release it is based on.
+///
+/// Understands git-describe output (`v1.4.0-12-gabc1234`, optionally with a
+/// `-dirty` suffix) and semver build metadata (`1.4.0+20241010.sha.abc1234`).
+/// Anything else is returned unchanged as the base version.
+pub fn normalize_version(version: &str) -> NormalizedVersion {
+    let version = version.trim();
+
+    let (rest, build_metadata) = match version.split_once('+') {
+        Some((rest, build)) if !build.is_empty() => (rest, Some(build.to_string())),
+        _ => (version, None),
+    };
+
+    let mut normalized = NormalizedVersion {
+        base: rest.to_string(),
+        commits_past: 0,
+        commit: build_metadata
+            .as_deref()
+            .and_then(commit_from_build_metadata),
+        build_metadata,
+        dirty: false,
+    };
+
+    if let Some(captures) = GIT_DESCRIBE_SUFFIX.captures(rest) {
+        normalized.base = captures["base"].to_string();
+        normalized.commits_past = captures["commits"].parse().unwrap_or_default();
+        normalized.commit = Some(captures["sha"].to_string());
+        normalized.dirty = captures.name("dirty").is_some();
+    } else if let Some(base) = rest.strip_suffix("-dirty") {
+        normalized.base = base.to_string();
+        normalized.dirty = true;
+    }
+
+    normalized
+}
+
+/// Finds a commit SHA in build metadata such as "20241010.sha.abc1234" or "gabc1234".
+fn commit_from_build_metadata(build: &str) -> Option<String> {
+    let is_sha =
+        |s: &str| s.len() >= 7 && s.len() <= 40 && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    let identifiers: Vec<&str> = build.split('.').collect();
+    identifiers.iter().enumerate().find_map(|(i, identifier)| {
+        if matches!(*identifier, "sha" | "git" | "commit") {
+            identifiers
+                .get(i + 1)
+                .filter(|next| is_sha(next))
+                .map(|s| s.to_string())
+        } else {
+            identifier
+                .strip_prefix('g')
+                .filter(|sha| is_sha(sha))
+                .map(str::to_string)
+        }
+    })
+}
+
+/// Parses a version string leniently into a semantic version.
+///
+/// Accepts a leading 'v' and missing minor/patch components ("v2", "1.4"),
+/// which are common in release tags but rejected by strict semver parsing.
+pub(crate) fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+
+    if let Ok(parsed) = Version::parse(version) {
+        return Some(parsed);
+    }
+
+    let split = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, rest) = version.split_at(split);
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.is_empty()
+        || parts.len() > 3
+        || parts
+            .iter()
+            .any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let mut padded = parts.join(".");
+    for _ in parts.len()..3 {
+        padded.push_str(".0");
+    }
+    padded.push_str(rest);
+    Version::parse(&padded).ok()
+}
+
+/// Classifies the jump from the current version to the latest version.
+///
+/// Returns None if the versions are identical.
+pub(crate) fn classify_update(current: &Version, latest: &Version) -> Option<UpdateKind> {
+    if current.major != latest.major {
+        Some(UpdateKind::Major)
+    } else if current.minor != latest.minor {
+        Some(UpdateKind::Minor)
+    } else if current.patch != latest.patch {
+        Some(UpdateKind::Patch)
+    } else if current.pre != latest.pre {
+        Some(UpdateKind::Prerelease)
+    } else {
+        None
+    }
+}
+
+/// Compiled form of an [`UpdateScope`].
+#[derive(Debug, Clone)]
+pub(crate) enum ScopeMatcher {
+    Any,
+    SameMajor,
+    SameMinor,
+    MaxVersion(Version),
+    Requirement(VersionReq),
+}
+
+impl ScopeMatcher {
+    /// Compiles an update scope, validating version caps and requirements up front.
+    pub(crate) fn new(scope: &UpdateScope) -> Result<Self> {
+        let invalid = |source: &str| ReleaseNotifierError::InvalidUpdateScope(source.to_string());
+        match scope {
+            UpdateScope::Any => Ok(Self::Any),
+            UpdateScope::SameMajor => Ok(Self::SameMajor),
+            UpdateScope::SameMinor => Ok(Self::SameMinor),
+            UpdateScope::MaxVersion(max) => parse_version(max)
+                .map(Self::MaxVersion)
+                .ok_or_else(|| invalid(max)),
+            UpdateScope::Requirement(req) => VersionReq::parse(req)
+                .map(Self::Requirement)
+                .map_err(|_| invalid(req)),
+        }
+    }
+
+    /// Returns true if a release with the given version is inside the scope.
+    ///
+    /// Scopes relative to the current version admit nothing when the current
+    /// version can't be parsed, and releases without a parseable version are
+    /// only admitted by [`UpdateScope::Any`].
+    pub(crate) fn allows(&self, current: Option<&Version>, candidate: Option<&Version>) -> bool {
+        let Some(candidate) = candidate else {
+            return matches!(self, Self::Any);
+        };
+
+        match self {
+            Self::Any => true,
+            Self::SameMajor => current.is_some_and(|c| c.major == candidate.major),
+            Self::SameMinor => {
+                current.is_some_and(|c| c.major == candidate.major && c.minor == candidate.minor)
+            }
+            Self::MaxVersion(max) => candidate <= max,
+            Self::Requirement(req) => req.matches(candidate),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_matcher() {
+        let matcher = TagMatcher::new(&TagPattern::Prefix("cli-".to_string())).unwrap();
+
+        assert!(matcher.matches("cli-v1.4.0"));
+        assert!(!matcher.matches("server-v2.1.0"));
+        assert_eq!(normalize_tag("cli-v1.4.0", Some(&matcher)), "1.4.0");
+        assert_eq!(normalize_tag("v1.4.0", Some(&matcher)), "1.4.0");
+    }
+
+    #[test]
+    fn test_regex_matcher() {
+        let matcher = TagMatcher::new(&TagPattern::Regex(
+            r"^sdk-js-v?(\d+\.\d+\.\d+.*)$".to_string(),
+        ))
+        .unwrap();
+
+        assert!(matcher.matches("sdk-js-v0.9.0"));
+        assert!(!matcher.matches("cli-v1.4.0"));
+        assert_eq!(normalize_tag("sdk-js-v0.9.0", Some(&matcher)), "0.9.0");
+    }
+
+    #[test]
+    fn test_regex_matcher_named_group() {
+        let matcher = TagMatcher::new(&TagPattern::Regex(
+            r"^(server|cli)-v(?P<version>.+)$".to_string(),
+        ))
+        .unwrap();
+
+        assert_eq!(matcher.extract("server-v2.1.0"), Some("2.1.0"));
+    }
+
+    #[test]
+    fn test_invalid_regex_patterns() {
+        for source in ["(unclosed", "^cli-v.*$"] {
+            let result = TagMatcher::new(&TagPattern::Regex(source.to_string()));
+            assert!(
+                matches!(result, Err(ReleaseNotifierError::InvalidTagPattern(_))),
+                "Expected '{}' to be rejected",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_version_lenient() {
+        assert_eq!(parse_version("v1.4.0"), Some(Version::new(1, 4, 0)));
+        assert_eq!(parse_version("1.4"), Some(Version::new(1, 4, 0)));
+        assert_eq!(parse_version("v2"), Some(Version::new(2, 0, 0)));
+        assert_eq!(
+            parse_version("2.1-beta.1"),
+            Some(Version::parse("2.1.0-beta.1").unwrap())
+        );
+        assert_eq!(parse_version("nightly"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+    }
+
+    #[test]
+    fn test_normalize_git_describe() {
+        let normalized = normalize_version("v1.4.0-12-gabc1234");
+        assert_eq!(normalized.base, "v1.4.0");
+        assert_eq!(normalized.commits_past, 12);
+        assert_eq!(normalized.commit.as_deref(), Some("abc1234"));
+        assert!(!normalized.dirty);
+        assert_eq!(normalized.to_string(), "12 commits past v1.4.0");
+
+        let normalized = normalize_version("1.4.0-rc.1-3-gdeadbeef-dirty");
+        assert_eq!(normalized.base, "1.4.0-rc.1");
+        assert_eq!(normalized.commits_past, 3);
+        assert!(normalized.dirty);
+    }
+
+    #[test]
+    fn test_normalize_build_metadata() {
+        let normalized = normalize_version("1.4.0+20241010.sha.abc1234");
+        assert_eq!(normalized.base, "1.4.0");
+        assert_eq!(normalized.commits_past, 0);
+        assert_eq!(normalized.commit.as_deref(), Some("abc1234"));
+        assert_eq!(
+            normalized.build_metadata.as_deref(),
+            Some("20241010.sha.abc1234")
+        );
+        assert_eq!(normalized.to_string(), "1.4.0");
+    }
+
+    #[test]
+    fn test_normalize_plain_versions_unchanged() {
+        for version in ["1.4.0", "v2.0.0-beta.1", "nightly"] {
+            let normalized = normalize_version(version);
+            assert_eq!(normalized.base, version);
+            assert_eq!(normalized.commits_past, 0);
+            assert_eq!(normalized.commit, None);
+        }
+    }
+
+    #[test]
+    fn test_classify_update() {
+        let classify = |current: &str, latest: &str| {
+            classify_update(
+                &parse_version(current).unwrap(),
+                &parse_version(latest).unwrap(),
+            )
+        };
+
+        assert_eq!(classify("1.9.0", "2.0.0"), Some(UpdateKind::Major));
+        assert_eq!(classify("1.2.3", "1.3.0"), Some(UpdateKind::Minor));
+        assert_eq!(classify("1.2.3", "1.2.4"), Some(UpdateKind::Patch));
+        assert_eq!(
+            classify("2.0.0-beta.1", "2.0.0"),
+            Some(UpdateKind::Prerelease)
+        );
+        assert_eq!(classify("2.0.0", "v2.0.0"), None);
+    }
+
+    #[test]
+    fn test_scope_matcher() {
+        let current = Version::new(3, 2, 1);
+        let allows = |scope: UpdateScope, candidate: &str| {
+            ScopeMatcher::new(&scope)
+                .unwrap()
+                .allows(Some(&current), parse_version(candidate).as_ref())
+        };
+
+        assert!(allows(UpdateScope::SameMajor, "3.9.0"));
+        assert!(!allows(UpdateScope::SameMajor, "4.0.0"));
+        assert!(allows(UpdateScope::SameMinor, "3.2.7"));
+        assert!(!allows(UpdateScope::SameMinor, "3.3.0"));
+        assert!(allows(UpdateScope::MaxVersion("3.5".to_string()), "3.5.0"));
+        assert!(!allows(UpdateScope::MaxVersion("3.5".to_string()), "3.5.1"));
+        assert!(allows(
+            UpdateScope::Requirement(">=3.0, <4.0".to_string()),
+            "3.8.0"
+        ));
+        assert!(!allows(
+            UpdateScope::Requirement(">=3.0, <4.0".to_string()),
+            "4.0.0"
+        ));
+    }
+
+    #[test]
+    fn test_scope_matcher_unparseable_current() {
+        let matcher = ScopeMatcher::new(&UpdateScope::SameMajor).unwrap();
+        assert!(!matcher.allows(None, parse_version("3.0.0").as_ref()));
+
+        let matcher = ScopeMatcher::new(&UpdateScope::Any).unwrap();
+        assert!(matcher.allows(None, None));
+    }
+}
@@ -1,6 +1,17 @@
-use relnotify::{ReleaseNotifier, ReleaseNotifierConfig};
+use chrono::Utc;
+use relnotify::testing::ManualClock;
+use relnotify::Clock;
+use relnotify::{
+    build_changelog, CachePolicy, CacheStore, ChangelogOptions, Channel, ChannelSource, DataSource,
+    FailureKind, MemoryStore, RefreshGuard, ReleaseNotifier, ReleaseNotifierConfig,
+    ReleaseNotifierError, RepoCache, RepoKey, TagPattern, UpdateKind, UpdateScope, UpdateStatus,
+    VersionFallback,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tempfile::NamedTempFile;
-use wiremock::matchers::{header, method, path};
+use wiremock::matchers::{header, method, path, path_regex};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn mock_releases_json() -> serde_json::Value {
@@ -149,6 +160,7 @@ async fn test_check_version_update_available() {
         .await;
 
     let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
         .check_interval(0)
         .base_url(mock_server.uri());
 
@@ -172,6 +184,7 @@ async fn test_check_version_no_update_when_on_latest() {
         .await;
 
     let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
         .check_interval(0)
         .base_url(mock_server.uri());
 
@@ -193,6 +206,7 @@ async fn test_check_version_handles_missing_v_prefix() {
         .await;
 
     let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
         .check_interval(0)
         .base_url(mock_server.uri());
 
@@ -214,6 +228,7 @@ async fn test_check_version_unknown_version_returns_no_update() {
         .await;
 
     let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
         .check_interval(0)
         .base_url(mock_server.uri());
 
@@ -425,3 +440,2062 @@ async fn test_token_is_sent_in_header() {
 
     assert!(release.is_some());
 }
+
+/// Creates a notifier for "test/repo" served from `base_url`, after
+/// `configure` adjusts its config.
+///
+/// The generic opt-outs are off, so checks run in CI and with `DO_NOT_TRACK` set.
+fn build_notifier(
+    base_url: &str,
+    configure: impl FnOnce(ReleaseNotifierConfig) -> ReleaseNotifierConfig,
+) -> ReleaseNotifier {
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .base_url(base_url);
+    ReleaseNotifier::new(configure(config)).unwrap()
+}
+
+/// Serves `releases` for any number of requests.
+async fn mount_releases(mock_server: &MockServer, releases: serde_json::Value) {
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(releases))
+        .mount(mock_server)
+        .await;
+}
+
+/// Serves `first` once, then `then` for every later request.
+async fn mount_releases_sequence(
+    mock_server: &MockServer,
+    first: ResponseTemplate,
+    then: ResponseTemplate,
+) {
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(first)
+        .up_to_n_times(1)
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(then)
+        .mount(mock_server)
+        .await;
+}
+
+fn mock_monorepo_releases_json() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "tag_name": "sdk-js-v0.9.0",
+            "name": "JS SDK 0.9.0",
+            "body": "SDK release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/sdk-js-v0.9.0",
+            "published_at": "2024-03-20T10:00:00Z"
+        },
+        {
+            "tag_name": "server-v2.1.0",
+            "name": "Server 2.1.0",
+            "body": "Server release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/server-v2.1.0",
+            "published_at": "2024-03-15T10:00:00Z"
+        },
+        {
+            "tag_name": "cli-v1.4.0",
+            "name": "CLI 1.4.0",
+            "body": "CLI release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/cli-v1.4.0",
+            "published_at": "2024-03-10T10:00:00Z"
+        },
+        {
+            "tag_name": "cli-v1.3.0",
+            "name": "CLI 1.3.0",
+            "body": "Older CLI release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/cli-v1.3.0",
+            "published_at": "2024-02-01T10:00:00Z"
+        }
+    ])
+}
+
+#[tokio::test]
+async fn test_tag_prefix_filters_releases() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_monorepo_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .tag_pattern(TagPattern::Prefix("cli-".to_string()))
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let release = notifier.get_latest_release(false).await.unwrap();
+
+    // The SDK release is newer, but only CLI tags should be considered
+    assert_eq!(release.unwrap().tag_name, "cli-v1.4.0");
+}
+
+#[tokio::test]
+async fn test_tag_prefix_strips_prefix_for_version_check() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_monorepo_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .tag_pattern(TagPattern::Prefix("cli-".to_string()))
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    // Plain version numbers match the prefixed tags
+    let result = notifier.check_version("1.3.0", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "cli-v1.4.0");
+
+    let result = notifier.check_version("v1.4.0", false).await.unwrap();
+    assert!(!result.update_available);
+}
+
+#[tokio::test]
+async fn test_tag_regex_filters_releases() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_monorepo_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .tag_pattern(TagPattern::Regex(r"^server-v(\d+\.\d+\.\d+)$".to_string()))
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "server-v2.1.0");
+
+    let result = notifier.check_version("2.1.0", false).await.unwrap();
+    assert!(!result.update_available);
+}
+
+fn mock_lts_releases_json() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "tag_name": "v4.0.0",
+            "name": "Version 4.0.0",
+            "body": "New major version",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v4.0.0",
+            "published_at": "2024-05-01T10:00:00Z"
+        },
+        {
+            "tag_name": "v3.2.0",
+            "name": "Version 3.2.0",
+            "body": "LTS minor release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v3.2.0",
+            "published_at": "2024-04-01T10:00:00Z"
+        },
+        {
+            "tag_name": "v3.1.1",
+            "name": "Version 3.1.1",
+            "body": "LTS patch release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v3.1.1",
+            "published_at": "2024-03-01T10:00:00Z"
+        },
+        {
+            "tag_name": "v3.1.0",
+            "name": "Version 3.1.0",
+            "body": "LTS release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v3.1.0",
+            "published_at": "2024-02-01T10:00:00Z"
+        }
+    ])
+}
+
+#[tokio::test]
+async fn test_update_scope_same_major() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .check_interval(0)
+            .update_scope(UpdateScope::SameMajor)
+    });
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.2.0");
+    assert_eq!(result.out_of_scope_release.unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_update_scope_same_minor() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .check_interval(0)
+            .update_scope(UpdateScope::SameMinor)
+    });
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.1.1");
+    assert_eq!(result.out_of_scope_release.unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_update_scope_max_version() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .check_interval(0)
+            .update_scope(UpdateScope::MaxVersion("3.1.9".to_string()))
+    });
+
+    let result = notifier.check_version("3.1.1", false).await.unwrap();
+    assert!(!result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.1.1");
+    assert!(result.out_of_scope_release.is_some());
+}
+
+#[tokio::test]
+async fn test_update_scope_requirement() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .check_interval(0)
+            .update_scope(UpdateScope::Requirement(">=3.0, <4.0".to_string()))
+    });
+
+    let result = notifier.check_version("v3.2.0", false).await.unwrap();
+    assert!(!result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.2.0");
+    assert_eq!(result.out_of_scope_release.unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_update_scope_any_has_no_out_of_scope_release() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v4.0.0");
+    assert!(result.out_of_scope_release.is_none());
+}
+
+#[tokio::test]
+async fn test_out_of_scope_release_published_before_backport() {
+    let mock_server = MockServer::start().await;
+
+    // The 3.9.1 backport is published after 4.0.0
+    let release = |tag: &str, published_at: &str| {
+        serde_json::json!({
+            "tag_name": tag,
+            "name": null,
+            "body": null,
+            "prerelease": false,
+            "draft": false,
+            "html_url": format!("https://github.com/test/repo/releases/tag/{}", tag),
+            "published_at": published_at
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            release("v3.9.1", "2024-03-01T10:00:00Z"),
+            release("v4.0.0", "2024-02-01T10:00:00Z"),
+            release("v3.9.0", "2024-01-01T10:00:00Z"),
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .update_scope(UpdateScope::SameMajor)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("3.9.0", false).await.unwrap();
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.9.1");
+    assert_eq!(result.out_of_scope_release.unwrap().tag_name, "v4.0.0");
+
+    // Nothing outside the scope is newer than a 5.x build
+    let result = notifier.check_version("5.0.0", false).await.unwrap();
+    assert!(result.out_of_scope_release.is_none());
+}
+
+#[tokio::test]
+async fn test_update_kind_and_versions_behind() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert_eq!(result.update_kind, Some(UpdateKind::Major));
+    assert_eq!(result.versions_behind, 3);
+
+    let result = notifier.check_version("3.2.0", false).await.unwrap();
+    assert_eq!(result.update_kind, Some(UpdateKind::Major));
+    assert_eq!(result.versions_behind, 1);
+}
+
+#[tokio::test]
+async fn test_update_kind_within_scope() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .check_interval(0)
+            .update_scope(UpdateScope::SameMajor)
+    });
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert_eq!(result.update_kind, Some(UpdateKind::Minor));
+    // v3.1.1 and v3.2.0; v4.0.0 is out of scope
+    assert_eq!(result.versions_behind, 2);
+
+    let result = notifier.check_version("3.1.1", false).await.unwrap();
+    assert_eq!(result.update_kind, Some(UpdateKind::Minor));
+    assert_eq!(result.versions_behind, 1);
+}
+
+#[tokio::test]
+async fn test_update_kind_absent_when_up_to_date() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let result = notifier.check_version("4.0.0", false).await.unwrap();
+    assert!(!result.update_available);
+    assert_eq!(result.update_kind, None);
+    assert_eq!(result.versions_behind, 0);
+}
+
+#[tokio::test]
+async fn test_releases_since() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let releases = notifier.releases_since("v3.1.0").await.unwrap();
+    let tags: Vec<&str> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(tags, ["v4.0.0", "v3.2.0", "v3.1.1"]);
+
+    let releases = notifier.releases_since("4.0.0").await.unwrap();
+    assert!(releases.is_empty());
+}
+
+#[tokio::test]
+async fn test_releases_since_excludes_drafts_and_includes_prereleases() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let releases = notifier.releases_since("1.0.0").await.unwrap();
+    let tags: Vec<&str> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(tags, ["v2.1.0-beta.1", "v2.0.0"]);
+
+    let changelog = build_changelog(&releases, &ChangelogOptions::new().skip_prereleases(true));
+    assert_eq!(changelog, "## v2.0.0 (2024-03-15)\n\nLatest stable release");
+}
+
+#[tokio::test]
+async fn test_releases_since_unknown_version() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let releases = notifier.releases_since("0.0.1-dev").await.unwrap();
+    assert!(releases.is_empty());
+}
+
+#[tokio::test]
+async fn test_update_status_reasons() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpdateAvailable);
+
+    let result = notifier.check_version("2.0.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+
+    let result = notifier.check_version("999.0.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UnknownCurrentVersion);
+
+    // The beta was published after the latest stable release
+    let result = notifier.check_version("2.1.0-beta.1", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::AheadOfLatest);
+    assert!(!result.update_available);
+}
+
+#[tokio::test]
+async fn test_update_status_no_releases() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+
+    assert_eq!(result.reason, UpdateStatus::NoReleases);
+    assert!(result.latest_release.is_none());
+}
+
+#[tokio::test]
+async fn test_update_status_indeterminate_without_publish_date() {
+    let mock_server = MockServer::start().await;
+
+    let releases = serde_json::json!([
+        {
+            "tag_name": "v1.1.0",
+            "name": "Version 1.1.0",
+            "body": "Latest release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v1.1.0",
+            "published_at": "2024-02-01T10:00:00Z"
+        },
+        {
+            "tag_name": "v1.0.0",
+            "name": "Version 1.0.0",
+            "body": "Release without a publish date",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v1.0.0",
+            "published_at": null
+        }
+    ]);
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(releases))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+
+    assert_eq!(result.reason, UpdateStatus::Indeterminate);
+    assert!(!result.update_available);
+}
+
+#[tokio::test]
+async fn test_version_fallback_compare_versions() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .version_fallback(VersionFallback::CompareVersions)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    // v3.0.0 isn't among the fetched releases
+    let result = notifier.check_version("3.0.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpdateAvailable);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v4.0.0");
+    assert_eq!(result.versions_behind, 4);
+
+    let result = notifier.check_version("5.0.0-dev", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::AheadOfLatest);
+
+    let result = notifier
+        .check_version("nightly-build", false)
+        .await
+        .unwrap();
+    assert_eq!(result.reason, UpdateStatus::UnknownCurrentVersion);
+
+    let releases = notifier.releases_since("3.1.5").await.unwrap();
+    let tags: Vec<&str> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(tags, ["v4.0.0", "v3.2.0"]);
+}
+
+#[tokio::test]
+async fn test_version_fallback_lookup_tag() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases/tags/v2.9.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "tag_name": "v2.9.0",
+            "name": "Version 2.9.0",
+            "body": "Old release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.9.0",
+            "published_at": "2023-06-01T10:00:00Z"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .version_fallback(VersionFallback::LookupTag)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("2.9.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpdateAvailable);
+    assert_eq!(result.versions_behind, 4);
+}
+
+#[tokio::test]
+async fn test_version_fallback_lookup_tag_not_found() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    // Both "v1.0.0-dev" and "1.0.0-dev" are tried
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/repos/test/repo/releases/tags/"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .version_fallback(VersionFallback::LookupTag)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("1.0.0-dev", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UnknownCurrentVersion);
+    assert!(!result.update_available);
+}
+
+#[tokio::test]
+async fn test_version_fallback_lookup_tag_is_cached() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Not finding the tag is cached too, so only the first check looks it up
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/repos/test/repo/releases/tags/"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(3600000)
+        .version_fallback(VersionFallback::LookupTag)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    for _ in 0..3 {
+        let result = notifier.check_version("0.9.0-dev", false).await.unwrap();
+        assert_eq!(result.reason, UpdateStatus::UnknownCurrentVersion);
+    }
+}
+
+#[tokio::test]
+async fn test_version_fallback_lookup_tag_failure_leaves_version_unknown() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/repos/test/repo/releases/tags/"))
+        .respond_with(ResponseTemplate::new(403).set_body_string("rate limit exceeded"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(3600000)
+        .version_fallback(VersionFallback::LookupTag)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    // The failed lookup backs off instead of being retried on the next check
+    for _ in 0..2 {
+        let result = notifier.check_version("0.9.0-dev", false).await.unwrap();
+        assert_eq!(result.reason, UpdateStatus::UnknownCurrentVersion);
+    }
+}
+
+fn mock_channel_releases_json() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "tag_name": "v2.1.0-nightly.20240325",
+            "name": "Nightly",
+            "body": "Nightly build",
+            "prerelease": true,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.1.0-nightly.20240325",
+            "published_at": "2024-03-25T10:00:00Z"
+        },
+        {
+            "tag_name": "v2.1.0-rc.1",
+            "name": "Version 2.1.0 RC 1",
+            "body": "Release candidate",
+            "prerelease": true,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.1.0-rc.1",
+            "published_at": "2024-03-20T10:00:00Z"
+        },
+        {
+            "tag_name": "v2.1.0-beta.1",
+            "name": "Version 2.1.0 Beta 1",
+            "body": "Beta release",
+            "prerelease": true,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.1.0-beta.1",
+            "published_at": "2024-03-10T10:00:00Z"
+        },
+        {
+            "tag_name": "v2.0.0",
+            "name": "Version 2.0.0",
+            "body": "Stable release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.0.0",
+            "published_at": "2024-03-01T10:00:00Z"
+        }
+    ])
+}
+
+#[tokio::test]
+async fn test_get_latest_on_channel() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_channel_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| config.check_interval(0));
+
+    let latest = |channel| notifier.get_latest_on_channel(channel);
+    assert_eq!(
+        latest(Channel::Nightly).await.unwrap().unwrap().tag_name,
+        "v2.1.0-nightly.20240325"
+    );
+    assert_eq!(
+        latest(Channel::Beta).await.unwrap().unwrap().tag_name,
+        "v2.1.0-rc.1"
+    );
+    assert_eq!(
+        latest(Channel::Stable).await.unwrap().unwrap().tag_name,
+        "v2.0.0"
+    );
+}
+
+#[tokio::test]
+async fn test_check_version_on_channel_skips_nightlies() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_channel_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| config.check_interval(0));
+
+    let result = notifier
+        .check_version_on_channel("2.1.0-beta.1", Channel::Beta)
+        .await
+        .unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v2.1.0-rc.1");
+    assert_eq!(result.update_kind, Some(UpdateKind::Prerelease));
+
+    let result = notifier
+        .check_version_on_channel("2.1.0-rc.1", Channel::Rc)
+        .await
+        .unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+
+    // Nothing newer has reached the stable channel
+    let result = notifier
+        .check_version_on_channel("2.0.0", Channel::Stable)
+        .await
+        .unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+}
+
+#[tokio::test]
+async fn test_check_version_on_channel_with_tag_patterns() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {
+                "tag_name": "insiders-42",
+                "name": "Insiders build 42",
+                "body": "Insiders build",
+                "prerelease": true,
+                "draft": false,
+                "html_url": "https://github.com/test/repo/releases/tag/insiders-42",
+                "published_at": "2024-03-20T10:00:00Z"
+            },
+            {
+                "tag_name": "insiders-41",
+                "name": "Insiders build 41",
+                "body": "Insiders build",
+                "prerelease": true,
+                "draft": false,
+                "html_url": "https://github.com/test/repo/releases/tag/insiders-41",
+                "published_at": "2024-03-10T10:00:00Z"
+            }
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .channel_source(ChannelSource::TagPatterns(vec![(
+            Channel::Beta,
+            "^insiders-".to_string(),
+        )]))
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier
+        .check_version_on_channel("insiders-41", Channel::Beta)
+        .await
+        .unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "insiders-42");
+}
+
+fn mock_backport_releases_json() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "tag_name": "v2.9.5",
+            "name": "Version 2.9.5",
+            "body": "Backported security fix",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v2.9.5",
+            "published_at": "2024-03-10T10:00:00Z"
+        },
+        {
+            "tag_name": "v3.1.0",
+            "name": "Version 3.1.0",
+            "body": "Latest release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v3.1.0",
+            "published_at": "2024-03-01T10:00:00Z"
+        },
+        {
+            "tag_name": "v3.0.0",
+            "name": "Version 3.0.0",
+            "body": "Major release",
+            "prerelease": false,
+            "draft": false,
+            "html_url": "https://github.com/test/repo/releases/tag/v3.0.0",
+            "published_at": "2024-02-01T10:00:00Z"
+        }
+    ])
+}
+
+async fn mount_backport_releases(mock_server: &MockServer, latest_expected_calls: u64) {
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_backport_releases_json()))
+        .mount(mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases/latest"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(mock_backport_releases_json()[1].clone()),
+        )
+        .expect(latest_expected_calls)
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_designated_latest_release() {
+    let mock_server = MockServer::start().await;
+    mount_backport_releases(&mock_server, 1).await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .prefer_designated_latest(true)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    // The backport was published last, but v3.1.0 is marked as Latest
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v3.1.0");
+
+    // Served from cache the second time
+    let release = notifier
+        .get_latest_on_channel(Channel::Stable)
+        .await
+        .unwrap();
+    assert_eq!(release.unwrap().tag_name, "v3.1.0");
+}
+
+#[tokio::test]
+async fn test_designated_latest_disabled_by_default() {
+    let mock_server = MockServer::start().await;
+    mount_backport_releases(&mock_server, 0).await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.9.5");
+}
+
+#[tokio::test]
+async fn test_check_version_with_designated_latest() {
+    let mock_server = MockServer::start().await;
+    mount_backport_releases(&mock_server, 1).await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(3600000)
+        .prefer_designated_latest(true)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("3.0.0", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v3.1.0");
+    assert_eq!(result.update_kind, Some(UpdateKind::Minor));
+    assert_eq!(result.versions_behind, 1);
+
+    // The backport is published after v3.1.0 but is still an older version
+    let result = notifier.check_version("2.9.5", false).await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.update_kind, Some(UpdateKind::Major));
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+}
+
+#[tokio::test]
+async fn test_designated_latest_outside_scope() {
+    let mock_server = MockServer::start().await;
+    mount_backport_releases(&mock_server, 1).await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(3600000)
+        .prefer_designated_latest(true)
+        .update_scope(UpdateScope::SameMajor)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_version("2.9.4", false).await.unwrap();
+    assert_eq!(result.latest_release.unwrap().tag_name, "v2.9.5");
+    assert_eq!(result.out_of_scope_release.unwrap().tag_name, "v3.1.0");
+}
+
+#[tokio::test]
+async fn test_designated_latest_falls_back_when_missing() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_backport_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases/latest"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .prefer_designated_latest(true)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.9.5");
+}
+
+#[tokio::test]
+async fn test_check_version_git_describe() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let result = notifier
+        .check_version("v3.2.0-12-gabc1234", false)
+        .await
+        .unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.update_kind, Some(UpdateKind::Major));
+    assert_eq!(result.current_version.commits_past, 12);
+    assert_eq!(result.current_version.to_string(), "12 commits past v3.2.0");
+
+    // Commits past the latest release are ahead of it, not unknown
+    let result = notifier
+        .check_version("v4.0.0-3-gdeadbeef", false)
+        .await
+        .unwrap();
+    assert_eq!(result.reason, UpdateStatus::AheadOfLatest);
+}
+
+#[tokio::test]
+async fn test_check_version_build_metadata() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_lts_releases_json()).await;
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.check_interval(0).update_scope(UpdateScope::Any)
+    });
+
+    let result = notifier
+        .check_version("4.0.0+20241010.sha.abc1234", false)
+        .await
+        .unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+    assert_eq!(result.current_version.base, "4.0.0");
+    assert_eq!(result.current_version.commit.as_deref(), Some("abc1234"));
+
+    let releases = notifier.releases_since("3.2.0+build.5").await.unwrap();
+    assert_eq!(releases.len(), 1);
+}
+
+fn mock_compare_json() -> serde_json::Value {
+    serde_json::json!({
+        "html_url": "https://github.com/test/repo/compare/abc1234...main",
+        "status": "behind",
+        "ahead_by": 2,
+        "behind_by": 0,
+        "total_commits": 2,
+        "commits": [
+            {
+                "sha": "def5678",
+                "commit": {
+                    "committer": { "date": "2024-03-10T10:00:00Z" }
+                }
+            },
+            {
+                "sha": "fed9876",
+                "commit": {
+                    "committer": { "date": "2024-03-12T10:00:00Z" }
+                }
+            }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn test_check_commit_behind_branch() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/compare/abc1234...main"))
+        .and(header("Authorization", "Bearer test-token-123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_compare_json()))
+        .expect(1) // Second check is served from cache
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .token("test-token-123")
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let result = notifier.check_commit("abc1234", "main").await.unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.commits_behind, 2);
+    assert_eq!(
+        result.latest_commit_date.unwrap().to_rfc3339(),
+        "2024-03-12T10:00:00+00:00"
+    );
+    assert_eq!(
+        result.compare_url,
+        "https://github.com/test/repo/compare/abc1234...main"
+    );
+
+    let result = notifier.check_commit("abc1234", "main").await.unwrap();
+    assert_eq!(result.commits_behind, 2);
+}
+
+#[tokio::test]
+async fn test_check_commit_up_to_date() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/compare/fed9876...main"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "html_url": "https://github.com/test/repo/compare/fed9876...main",
+            "status": "identical",
+            "ahead_by": 0,
+            "behind_by": 0,
+            "total_commits": 0,
+            "commits": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let result = notifier.check_commit("fed9876", "main").await.unwrap();
+
+    assert!(!result.update_available);
+    assert_eq!(result.commits_behind, 0);
+    assert!(result.latest_commit_date.is_none());
+}
+
+#[tokio::test]
+async fn test_check_commit_disk_cache_persistence() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/compare/abc1234...main"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_compare_json()))
+        .expect(1) // Only called once - second notifier loads from disk
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+    for _ in 0..2 {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(mock_server.uri());
+
+        let notifier = ReleaseNotifier::new(config).unwrap();
+        let result = notifier.check_commit("abc1234", "main").await.unwrap();
+        assert_eq!(result.commits_behind, 2);
+    }
+}
+
+#[tokio::test]
+async fn test_check_commit_unknown_sha() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/compare/0000000...main"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(0)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let result = notifier.check_commit("0000000", "main").await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_new_async_loads_disk_cache() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(1) // Only called once - second notifier loads from disk
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+    for _ in 0..2 {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(mock_server.uri());
+
+        let notifier = ReleaseNotifier::new_async(config).await.unwrap();
+        let release = notifier.get_latest_release(false).await.unwrap();
+        assert_eq!(release.unwrap().tag_name, "v2.0.0");
+    }
+}
+
+#[tokio::test]
+async fn test_new_async_validates_config() {
+    let config = ReleaseNotifierConfig::new("invalid-repo");
+    assert!(ReleaseNotifier::new_async(config).await.is_err());
+}
+
+#[tokio::test]
+async fn test_disk_cache_shared_between_repos() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/other/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+    let notifier_for = |repo: &str| {
+        let config = ReleaseNotifierConfig::new(repo)
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(mock_server.uri());
+        ReleaseNotifier::new(config).unwrap()
+    };
+
+    // Both repos write to the same file without overwriting each other
+    for _ in 0..2 {
+        let repo = notifier_for("test/repo").get_latest_release(false).await;
+        let other = notifier_for("test/other").get_latest_release(false).await;
+        assert_eq!(repo.unwrap().unwrap().tag_name, "v2.0.0");
+        assert_eq!(other.unwrap().unwrap().tag_name, "v4.0.0");
+    }
+
+    // Clearing one repo's cache leaves the other's entries in place
+    notifier_for("test/repo").clear_cache();
+    let other = notifier_for("test/other").get_latest_release(false).await;
+    assert_eq!(other.unwrap().unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_concurrent_refresh_fetches_once() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(mock_releases_json())
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1) // The second notifier waits for the lock and reuses the result
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+    let notifier_for = || {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(mock_server.uri());
+        ReleaseNotifier::new(config).unwrap()
+    };
+    let (first, second) = (notifier_for(), notifier_for());
+
+    let (first, second) = tokio::join!(
+        first.get_latest_release(false),
+        second.get_latest_release(false)
+    );
+    assert_eq!(first.unwrap().unwrap().tag_name, "v2.0.0");
+    assert_eq!(second.unwrap().unwrap().tag_name, "v2.0.0");
+}
+
+/// Fetches releases once with each of two notifiers sharing a cache store.
+async fn assert_shared_store_fetches_once(store: Arc<dyn CacheStore>) {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(1) // The second notifier is served from the shared store
+        .mount(&mock_server)
+        .await;
+
+    for _ in 0..2 {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .check_interval(3600000)
+            .cache_store(Arc::clone(&store))
+            .base_url(mock_server.uri());
+        let notifier = ReleaseNotifier::new(config).unwrap();
+
+        let release = notifier.get_latest_release(false).await.unwrap().unwrap();
+        assert_eq!(release.tag_name, "v2.0.0");
+    }
+}
+
+#[tokio::test]
+async fn test_shared_memory_store() {
+    assert_shared_store_fetches_once(Arc::new(MemoryStore::new())).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_shared_sqlite_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = relnotify::SqliteStore::open(dir.path().join("cache.db")).unwrap();
+    assert_shared_store_fetches_once(Arc::new(store)).await;
+}
+
+/// A store that counts writes and keeps records in memory.
+#[derive(Debug, Default)]
+struct CountingStore {
+    inner: MemoryStore,
+    puts: AtomicUsize,
+    locks: AtomicUsize,
+    write_locks: AtomicUsize,
+}
+
+impl CacheStore for CountingStore {
+    fn get(&self, key: &RepoKey) -> relnotify::Result<Option<RepoCache>> {
+        self.inner.get(key)
+    }
+
+    fn put(&self, key: &RepoKey, record: &RepoCache) -> relnotify::Result<()> {
+        self.puts.fetch_add(1, Ordering::SeqCst);
+        self.inner.put(key, record)
+    }
+
+    fn invalidate(&self, key: &RepoKey) -> relnotify::Result<()> {
+        self.inner.invalidate(key)
+    }
+
+    fn lock(&self, _key: &RepoKey) -> Option<RefreshGuard> {
+        self.locks.fetch_add(1, Ordering::SeqCst);
+        None
+    }
+
+    fn write_lock(&self, _key: &RepoKey) -> Option<RefreshGuard> {
+        self.write_locks.fetch_add(1, Ordering::SeqCst);
+        None
+    }
+}
+
+#[tokio::test]
+async fn test_custom_cache_store_takes_precedence_over_cache_file() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache_path = dir.path().join("cache.json");
+    let store = Arc::new(CountingStore::default());
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .cache_file_path(cache_path.to_str().unwrap())
+        .cache_store(store.clone())
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(store.puts.load(Ordering::SeqCst), 1);
+    assert!(!cache_path.exists());
+
+    notifier.clear_cache();
+    let key = RepoKey {
+        base_url: mock_server.uri(),
+        repo: "test/repo".to_string(),
+    };
+    assert!(store.get(&key).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_concurrent_checks_share_one_fetch() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(mock_releases_json())
+                .set_delay(Duration::from_millis(200)),
+        )
+        .expect(1) // Callers arriving during the fetch await its result
+        .mount(&mock_server)
+        .await;
+
+    // Caching is disabled, so only the in-flight request can be shared
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .check_interval(0)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let checks = (0..5).map(|_| {
+        let notifier = notifier.clone();
+        tokio::spawn(async move { notifier.check_version("1.0.0", false).await })
+    });
+    for check in checks.collect::<Vec<_>>() {
+        assert!(check.await.unwrap().unwrap().update_available);
+    }
+}
+
+#[tokio::test]
+async fn test_newer_cache_schema_is_not_overwritten() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+    let content = r#"{"schema_version":999,"entries":{}}"#;
+    std::fs::write(&cache_path, content).unwrap();
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .cache_file_path(&cache_path)
+        .base_url(mock_server.uri());
+
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.0.0");
+    // The fetched releases couldn't be saved
+    assert!(!notifier.cache_status().await.in_sync);
+
+    notifier.clear_cache();
+    assert_eq!(std::fs::read_to_string(&cache_path).unwrap(), content);
+}
+
+#[tokio::test]
+async fn test_disk_cache_keyed_by_base_url() {
+    let first_server = MockServer::start().await;
+    let second_server = MockServer::start().await;
+
+    for server in [&first_server, &second_server] {
+        Mock::given(method("GET"))
+            .and(path("/repos/test/repo/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+            .expect(1) // Cached data from the other server isn't reused
+            .mount(server)
+            .await;
+    }
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+
+    for server in [&first_server, &second_server] {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(server.uri());
+
+        let notifier = ReleaseNotifier::new(config).unwrap();
+        notifier.get_latest_release(false).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_strict_cache_policy_fails_when_expired_and_unreachable() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+        ResponseTemplate::new(500),
+    )
+    .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .cache_policy(CachePolicy::Strict)
+            .clock(clock.clone())
+    });
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+    assert!(!result.stale);
+    assert!(result.cache_age.is_none());
+
+    clock.advance(Duration::from_secs(3600));
+    assert!(notifier.check_version("1.0.0", false).await.is_err());
+}
+
+#[tokio::test]
+async fn test_stale_if_error_serves_expired_cache() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+        ResponseTemplate::new(500),
+    )
+    .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .cache_policy(CachePolicy::StaleIfError)
+            .clock(clock.clone())
+    });
+    notifier.check_version("1.0.0", false).await.unwrap();
+
+    clock.advance(Duration::from_secs(3600));
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+
+    assert!(result.stale);
+    assert_eq!(result.cache_age, Some(Duration::from_secs(3600)));
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v2.0.0");
+}
+
+#[tokio::test]
+async fn test_stale_if_error_refreshes_when_reachable() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+        ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()),
+    )
+    .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .cache_policy(CachePolicy::StaleIfError)
+            .clock(clock.clone())
+    });
+    notifier.check_version("1.0.0", false).await.unwrap();
+
+    clock.advance(Duration::from_secs(3600));
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+
+    assert!(!result.stale);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_stale_while_revalidate_refreshes_in_background() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+        ResponseTemplate::new(200).set_body_json(mock_lts_releases_json()),
+    )
+    .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config
+            .cache_policy(CachePolicy::StaleWhileRevalidate)
+            .clock(clock.clone())
+    });
+    notifier.check_version("1.0.0", false).await.unwrap();
+
+    clock.advance(Duration::from_secs(3600));
+
+    // The expired data is returned right away...
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+    assert!(result.stale);
+    assert!(result.cache_age.is_some());
+    assert_eq!(result.latest_release.unwrap().tag_name, "v2.0.0");
+
+    // ...while the refresh happens in the background
+    for _ in 0..50 {
+        if mock_server.received_requests().await.unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let result = notifier.check_version("3.1.0", false).await.unwrap();
+    assert!(!result.stale);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v4.0.0");
+}
+
+#[tokio::test]
+async fn test_failed_fetch_backs_off() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1) // The second check is paused instead of retried
+        .mount(&mock_server)
+        .await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+    let notifier_for = || {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .auto_disable(false)
+            .check_interval(3600000)
+            .cache_file_path(&cache_path)
+            .base_url(mock_server.uri());
+        ReleaseNotifier::new(config).unwrap()
+    };
+
+    let notifier = notifier_for();
+    assert!(notifier.backoff_status().is_none());
+
+    let result = notifier.check_version("1.0.0", false).await;
+    assert!(matches!(
+        result,
+        Err(ReleaseNotifierError::ApiError { status: 503, .. })
+    ));
+
+    let status = notifier.backoff_status().unwrap();
+    assert_eq!(status.kind, FailureKind::ServerError);
+    assert_eq!(status.consecutive_failures, 1);
+    assert_eq!(
+        status.retry_after - status.last_failure,
+        chrono::Duration::minutes(1)
+    );
+
+    let result = notifier.check_version("1.0.0", false).await;
+    assert!(matches!(
+        result,
+        Err(ReleaseNotifierError::BackingOff { kind: FailureKind::ServerError, until })
+            if until == status.retry_after
+    ));
+
+    // The backoff is persisted for the next process
+    let notifier = notifier_for();
+    assert_eq!(notifier.backoff_status(), Some(status));
+    assert!(notifier.check_version("1.0.0", false).await.is_err());
+}
+
+#[tokio::test]
+async fn test_backoff_classifies_missing_repo() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    assert!(notifier.get_latest_release(false).await.is_err());
+    assert_eq!(
+        notifier.backoff_status().unwrap().kind,
+        FailureKind::NotFound
+    );
+}
+
+#[tokio::test]
+async fn test_manual_clock_expires_cache() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| config.clock(clock.clone()));
+    notifier.get_latest_release(false).await.unwrap();
+
+    clock.advance(Duration::from_secs(3599));
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+    assert_eq!(result.cache_age, Some(Duration::from_secs(3599)));
+
+    clock.advance(Duration::from_secs(1));
+    notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(notifier.cache_status().await.last_fetch, Some(clock.now()));
+}
+
+#[tokio::test]
+async fn test_manual_clock_ends_backoff() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(503),
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+    )
+    .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| config.clock(clock.clone()));
+    assert!(notifier.get_latest_release(false).await.is_err());
+    assert_eq!(
+        notifier.backoff_status().unwrap().retry_after,
+        clock.now() + chrono::Duration::minutes(1)
+    );
+
+    clock.advance(Duration::from_secs(59));
+    assert!(matches!(
+        notifier.get_latest_release(false).await,
+        Err(ReleaseNotifierError::BackingOff { .. })
+    ));
+
+    clock.advance(Duration::from_secs(1));
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.0.0");
+}
+
+#[tokio::test]
+async fn test_check_version_cached_answers_from_cache_only() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(1) // Only the explicit refresh contacts GitHub
+        .mount(&mock_server)
+        .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| config.clock(clock.clone()));
+    assert!(notifier.check_version_cached("1.0.0", false).is_none());
+
+    notifier.refresh().await.unwrap();
+    notifier.refresh().await.unwrap(); // Still fresh, so not fetched again
+
+    // No async runtime is needed
+    let result = std::thread::spawn({
+        let notifier = notifier.clone();
+        move || notifier.check_version_cached("1.0.0", false)
+    })
+    .join()
+    .unwrap()
+    .unwrap();
+    assert!(result.update_available);
+    assert_eq!(result.latest_release.unwrap().tag_name, "v2.0.0");
+    assert!(!result.stale);
+
+    // Expired data is still served, marked stale
+    clock.advance(Duration::from_secs(7200));
+    let result = notifier.check_version_cached("2.0.0", false).unwrap();
+    assert_eq!(result.reason, UpdateStatus::UpToDate);
+    assert!(result.stale);
+    assert_eq!(result.cache_age, Some(Duration::from_secs(7200)));
+}
+
+#[tokio::test]
+async fn test_check_version_cached_needs_designated_latest_when_preferred() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases/latest"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()[2].clone()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .prefer_designated_latest(true)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    // Prerelease checks don't use the designated latest release
+    notifier.get_latest_prerelease().await.unwrap();
+    assert!(notifier.check_version_cached("1.0.0", true).is_some());
+    assert!(notifier.check_version_cached("1.0.0", false).is_none());
+
+    notifier.refresh().await.unwrap();
+    let result = notifier.check_version_cached("1.0.0", false).unwrap();
+    assert_eq!(result.latest_release.unwrap().tag_name, "v1.0.0");
+}
+
+/// Environment variables passing the mock server and cache file to the
+/// background refresh child process.
+const CHILD_BASE_URL_ENV: &str = "RELNOTIFY_TEST_CHILD_BASE_URL";
+const CHILD_CACHE_FILE_ENV: &str = "RELNOTIFY_TEST_CHILD_CACHE_FILE";
+
+/// The child side of `test_background_refresh_in_child_process`; does nothing
+/// when run as a normal test.
+#[tokio::test]
+async fn background_refresh_child() {
+    let (Ok(base_url), Ok(cache_path)) = (
+        std::env::var(CHILD_BASE_URL_ENV),
+        std::env::var(CHILD_CACHE_FILE_ENV),
+    ) else {
+        return;
+    };
+
+    let notifier = build_notifier(&base_url, |config| config.cache_file_path(cache_path));
+    assert!(notifier.is_background_refresh());
+    notifier.run_background_refresh().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_background_refresh_in_child_process() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let cache_path = dir.path().join("cache.json");
+    let cache_path = cache_path.to_str().unwrap();
+    let notifier = build_notifier(&mock_server.uri(), |config| {
+        config.cache_file_path(cache_path)
+    });
+    assert!(!notifier.is_background_refresh());
+
+    // Re-run this test binary as the child, limited to the child test
+    let child_command = || {
+        let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["background_refresh_child", "--exact"])
+            .env(CHILD_BASE_URL_ENV, mock_server.uri())
+            .env(CHILD_CACHE_FILE_ENV, cache_path);
+        command
+    };
+    assert!(notifier
+        .spawn_background_refresh_with(child_command())
+        .unwrap());
+
+    let mut result = None;
+    for _ in 0..100 {
+        result = notifier.check_version_cached("1.0.0", false);
+        if result.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(result.unwrap().update_available);
+
+    // The cache is now fresh, so no other child is started
+    assert!(!notifier
+        .spawn_background_refresh_with(child_command())
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_background_refresh_needs_shared_cache() {
+    let config = ReleaseNotifierConfig::new("test/repo").auto_disable(false);
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let command = std::process::Command::new("true");
+    assert!(!notifier.spawn_background_refresh_with(command).unwrap());
+
+    // A configured store the child can't read isn't shared either
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .cache_store(Arc::new(MemoryStore::new()));
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let command = std::process::Command::new("true");
+    assert!(!notifier.spawn_background_refresh_with(command).unwrap());
+}
+
+#[tokio::test]
+async fn test_should_notify_once_per_version_then_at_interval() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_releases_json()).await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| config.clock(clock.clone()));
+
+    let up_to_date = notifier.check_version("2.0.0", false).await.unwrap();
+    assert!(!notifier.should_notify(&up_to_date));
+
+    let stable = notifier.check_version("1.0.0", false).await.unwrap();
+    assert!(notifier.should_notify(&stable));
+    assert!(!notifier.should_notify(&stable));
+
+    // A different version is shown right away
+    let beta = notifier.check_version("1.0.0", true).await.unwrap();
+    assert_eq!(
+        beta.latest_release.as_ref().unwrap().tag_name,
+        "v2.1.0-beta.1"
+    );
+    assert!(notifier.should_notify(&beta));
+
+    // Reminders about the same version wait for the notify interval (1 day)
+    clock.advance(Duration::from_secs(23 * 3600));
+    assert!(!notifier.should_notify(&beta));
+    clock.advance(Duration::from_secs(3600));
+    assert!(notifier.should_notify(&beta));
+}
+
+#[tokio::test]
+async fn test_snooze_and_dismiss_notifications() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_releases_json()).await;
+
+    let cache_file = NamedTempFile::new().unwrap();
+    let cache_path = cache_file.path().to_str().unwrap().to_string();
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier_for = || {
+        let config = ReleaseNotifierConfig::new("test/repo")
+            .auto_disable(false)
+            .notify_interval(0)
+            .cache_file_path(&cache_path)
+            .clock(clock.clone())
+            .base_url(mock_server.uri());
+        ReleaseNotifier::new(config).unwrap()
+    };
+
+    let notifier = notifier_for();
+    let stable = notifier.check_version("1.0.0", false).await.unwrap();
+    let beta = notifier.check_version("1.0.0", true).await.unwrap();
+
+    notifier.snooze(Duration::from_secs(3600)).unwrap();
+    assert!(!notifier.should_notify(&stable));
+    clock.advance(Duration::from_secs(3600));
+    assert!(notifier.should_notify(&stable));
+
+    // Dismissals persist, survive clearing the cache, and only cover that version
+    notifier.dismiss("2.0.0").unwrap();
+    notifier.clear_cache();
+    let notifier = notifier_for();
+    assert!(!notifier.should_notify(&stable));
+    assert!(notifier.should_notify(&beta));
+
+    // Durations past the millisecond range snooze indefinitely
+    notifier.snooze(Duration::MAX).unwrap();
+    assert!(!notifier.should_notify(&beta));
+}
+
+#[tokio::test]
+async fn test_notification_state_is_updated_under_write_lock() {
+    let mock_server = MockServer::start().await;
+    mount_releases(&mock_server, mock_releases_json()).await;
+
+    let store = Arc::new(CountingStore::default());
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .auto_disable(false)
+        .cache_store(store.clone())
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+    let result = notifier.check_version("1.0.0", false).await.unwrap();
+
+    // Each update takes the short write lock, never the refresh lock
+    let locks_before = store.locks.load(Ordering::SeqCst);
+    let write_locks_before = store.write_locks.load(Ordering::SeqCst);
+    assert!(notifier.should_notify(&result));
+    notifier.snooze(Duration::from_secs(3600)).unwrap();
+    notifier.dismiss("2.0.0").unwrap();
+    notifier.clear_cache();
+    assert_eq!(store.locks.load(Ordering::SeqCst), locks_before);
+    assert_eq!(
+        store.write_locks.load(Ordering::SeqCst) - write_locks_before,
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_clear_cache_resets_backoff() {
+    let mock_server = MockServer::start().await;
+    mount_releases_sequence(
+        &mock_server,
+        ResponseTemplate::new(500),
+        ResponseTemplate::new(200).set_body_json(mock_releases_json()),
+    )
+    .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    assert!(notifier.get_latest_release(false).await.is_err());
+    assert!(notifier.backoff_status().is_some());
+
+    notifier.clear_cache();
+    assert!(notifier.backoff_status().is_none());
+
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.0.0");
+}
+
+#[tokio::test]
+async fn test_expired_cache_is_revalidated_with_etag() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(mock_releases_json()),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let clock = Arc::new(ManualClock::starting_now());
+    let notifier = build_notifier(&mock_server.uri(), |config| config.clock(clock.clone()));
+    notifier.get_latest_release(false).await.unwrap();
+    assert!(notifier.cache_status().await.has_etag);
+
+    clock.advance(Duration::from_secs(3600));
+    let release = notifier.get_latest_release(false).await.unwrap();
+    assert_eq!(release.unwrap().tag_name, "v2.0.0");
+
+    let status = notifier.cache_status().await;
+    assert_eq!(status.last_source, Some(DataSource::Revalidated));
+    assert!(status.next_fetch > clock.now());
+}
+
+#[tokio::test]
+async fn test_cache_status() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/repos/test/repo/releases"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_releases_json()))
+        .mount(&mock_server)
+        .await;
+
+    let config = ReleaseNotifierConfig::new("test/repo")
+        .check_interval(3600000)
+        .base_url(mock_server.uri());
+    let notifier = ReleaseNotifier::new(config).unwrap();
+
+    let status = notifier.cache_status().await;
+    assert_eq!(status.entries, 0);
+    assert!(status.last_fetch.is_none());
+    assert!(status.last_source.is_none());
+    assert!(status.next_fetch <= Utc::now());
+    assert!(status.in_sync);
+
+    notifier.get_latest_release(false).await.unwrap();
+    let status = notifier.cache_status().await;
+    assert_eq!(status.entries, 1);
+    assert_eq!(status.last_source, Some(DataSource::Network));
+    assert!(!status.has_etag);
+    let last_fetch = status.last_fetch.unwrap();
+    assert_eq!(status.next_fetch, last_fetch + chrono::Duration::hours(1));
+
+    notifier.get_latest_release(false).await.unwrap();
+    let status = notifier.cache_status().await;
+    assert_eq!(status.last_source, Some(DataSource::Cache));
+    assert_eq!(status.last_fetch, Some(last_fetch));
+}